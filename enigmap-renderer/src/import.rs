@@ -0,0 +1,169 @@
+use png::{ColorType, Decoder, DecodingError, Transformations};
+
+use std::fs::File;
+use std::path::Path;
+
+use enigmap::HexMap;
+
+use crate::renderers::{Image, ColorMode, colors::ColorMap};
+
+/// Converts painted or rendered images back into `HexMap`
+///
+/// Each `Hex` gets `HexType` with color closest to the color under its center.
+/// Image can have any size, it is stretched over the whole map.
+/// ## Usage
+/// ```no_run
+/// use enigmap::HexMap;
+/// use enigmap_renderer::import::Importer;
+///
+/// let mut hexmap = HexMap::new(100, 75);
+/// let importer = Importer::default();
+/// let report = importer.import_file("./painted.png", &mut hexmap).unwrap();
+/// for (x, y) in report.unmatched {
+///     println!("hex {}, {} has unknown color", x, y);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Importer {
+    /// Colormap used when matching colors
+    pub colors: ColorMap,
+    /// Maximum distance of colors in RGB space which is still considered a match
+    pub tolerance: f32,
+}
+
+impl Importer {
+    /// Sets `HexType` of every `Hex` in map based on the image
+    pub fn import(&self, image: &Image, map: &mut HexMap) -> ImportReport {
+        let palette = self.colors.iter().map(|(hex_type, color)| {
+            (*hex_type, [color.r * 255.0, color.g * 255.0, color.b * 255.0])
+        }).collect::<Vec<_>>();
+
+        let mut report = ImportReport{unmatched: Vec::new()};
+
+        if palette.is_empty() {
+            return report;
+        }
+
+        for hex in &mut map.field {
+            let (hex_center_x, hex_center_y) = hex.center();
+            let pixel_x = (hex_center_x / map.absolute_size_x * image.width() as f32).max(0.0).min(image.width() as f32 - 1.0) as u32;
+            let pixel_y = (hex_center_y / map.absolute_size_y * image.height() as f32).max(0.0).min(image.height() as f32 - 1.0) as u32;
+            let pixel = image.get_pixel(pixel_x, pixel_y);
+
+            let mut smallest_dist = f32::MAX;
+            let mut best_match = palette[0].0;
+            for (hex_type, color) in &palette {
+                let dist = ((f32::from(pixel[0]) - color[0]).powi(2) + (f32::from(pixel[1]) - color[1]).powi(2) + (f32::from(pixel[2]) - color[2]).powi(2)).sqrt();
+                if dist < smallest_dist {
+                    smallest_dist = dist;
+                    best_match = *hex_type;
+                }
+            }
+
+            hex.terrain_type = best_match;
+            if smallest_dist > self.tolerance {
+                report.unmatched.push((hex.x, hex.y));
+            }
+        }
+
+        report
+    }
+
+    /// Loads png image and sets `HexType` of every `Hex` in map based on it
+    ///
+    /// 16-bit images are reduced to 8 bits, palettes and low bit depths are expanded
+    pub fn import_file<P: AsRef<Path>>(&self, path: P, map: &mut HexMap) -> Result<ImportReport, DecodingError> {
+        let file = File::open(path)?;
+        let mut decoder = Decoder::new(file);
+        decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+        let (info, mut reader) = decoder.read_info()?;
+        let mut buf = vec![0; info.buffer_size()];
+        reader.next_frame(&mut buf)?;
+
+        // convert grayscale images into RGB
+        let image = match info.color_type {
+            ColorType::RGB => Image::from_buffer(info.width, info.height, buf, ColorMode::Rgb),
+            ColorType::RGBA => Image::from_buffer(info.width, info.height, buf, ColorMode::Rgba),
+            ColorType::Grayscale => {
+                let buf = buf.iter().flat_map(|&val| vec![val, val, val]).collect();
+                Image::from_buffer(info.width, info.height, buf, ColorMode::Rgb)
+            },
+            ColorType::GrayscaleAlpha => {
+                let buf = buf.chunks_exact(2).flat_map(|pixel| vec![pixel[0], pixel[0], pixel[0]]).collect();
+                Image::from_buffer(info.width, info.height, buf, ColorMode::Rgb)
+            },
+            ColorType::Indexed => return Err(DecodingError::Other("indexed images are not supported".into()))
+        };
+
+        Ok(self.import(&image, map))
+    }
+}
+
+impl Default for Importer {
+    fn default() -> Importer {
+        Importer{colors: ColorMap::new(), tolerance: 12.0}
+    }
+}
+
+/// Result of the import
+#[derive(Debug, Clone)]
+pub struct ImportReport {
+    /// Coordinates of hexes which color did not closely match any `HexType`
+    pub unmatched: Vec<(i32, i32)>,
+}
+
+impl ImportReport {
+    /// Returns `true` if all hexes matched some `HexType`
+    pub fn is_exact(&self) -> bool {
+        self.unmatched.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enigmap::HexType;
+    use crate::renderers::{Basic, Renderer};
+
+    #[test]
+    fn round_trip() {
+        let mut map = HexMap::new(20, 10);
+        for (index, hex) in map.field.iter_mut().enumerate() {
+            hex.terrain_type = HexType::from((index % 12) as i32);
+        }
+
+        let mut renderer = Basic::default();
+        renderer.set_scale(10.0);
+        renderer.set_random_colors(false);
+        renderer.use_antialiasing(false);
+        let image = renderer.render(&map);
+
+        let mut imported = HexMap::new(20, 10);
+        let report = Importer::default().import(&image, &mut imported);
+        assert!(report.is_exact());
+        for (original, imported) in map.field.iter().zip(imported.field.iter()) {
+            assert_eq!(original.terrain_type, imported.terrain_type);
+        }
+    }
+
+    #[test]
+    fn sixteen_bit_file() {
+        let importer = Importer::default();
+        let (r, g, b) = importer.colors.get_color_u8(&HexType::Forest);
+        let path = std::env::temp_dir().join("enigmap_import_16.png");
+        {
+            let file = File::create(&path).unwrap();
+            let mut encoder = png::Encoder::new(file, 1, 1);
+            encoder.set_color(ColorType::RGB);
+            encoder.set_depth(png::BitDepth::Sixteen);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[r, 0x80, g, 0x80, b, 0x80]).unwrap();
+        }
+
+        let mut map = HexMap::new(1, 1);
+        let report = importer.import_file(&path, &mut map);
+        std::fs::remove_file(&path).unwrap();
+        assert!(report.unwrap().is_exact());
+        assert_eq!(HexType::Forest, map.field[0].terrain_type);
+    }
+}
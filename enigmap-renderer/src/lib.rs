@@ -6,4 +6,5 @@ mod tests {
     }
 }
 
-pub mod renderers;
\ No newline at end of file
+pub mod renderers;
+pub mod import;
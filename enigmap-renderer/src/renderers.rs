@@ -1,21 +1,44 @@
 use enigmap::{HexMap, Hex, RATIO};
 
 mod basic;
+mod blend;
+mod decor;
+mod error;
+mod font;
+mod labels;
+mod legend;
 mod ogl;
+mod outline;
+mod regions;
+mod relief;
 mod sprite;
+mod textured;
+mod textures;
 mod vector;
 pub mod colors;
 pub mod image;
 
 pub use self::basic::Basic;
+pub use self::blend::Blend;
+pub use self::decor::DecorStyle;
+pub use self::error::RenderError;
+pub use self::labels::{Labels, Label, CoordinateFormat};
+pub use self::legend::Legend;
 pub use self::ogl::OGL;
+pub use self::outline::{Outline, OutlineMode};
+pub use self::relief::Relief;
 pub use self::sprite::*;
+pub use self::textured::Textured;
+pub use self::textures::Setting;
 pub use self::vector::Vector;
 pub use self::image::{Image, ColorMode};
 
 const HALF_RATIO: f32 = RATIO / 2.0;
 const QUARTER_RATIO: f32 = RATIO / 4.0;
 
+/// Neighbour offsets across hex edges, edge `i` goes from vertex `i` to vertex `i + 1`
+pub(crate) const EDGE_DIRECTIONS: [(i32, i32); 6] = [(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)];
+
 /// Returns `Hex` vertex positon in relative (non-multiplied) coordinates
 /// 
 /// Index starts on upper right vertex and continues clockwise
@@ -46,6 +69,20 @@ pub fn get_hex_vertex(hex: &Hex, index: usize) -> (f32, f32) {
     (coords.0, coords.1)
 }
 
+/// Returns index of hex on given coordinates, which may be outside of the map
+///
+/// When `wrap_map` is `false`, hexes over left and right map edge are not returned
+pub(crate) fn get_hex_index(map: &HexMap, x: i32, y: i32, wrap_map: bool) -> Option<usize> {
+    if y < 0 || y >= map.size_y as i32 {
+        return None;
+    }
+    let wrapped = Hex::unwrap_coords(x, y, map.size_x);
+    if !wrap_map && wrapped != (x, y) {
+        return None;
+    }
+    map.coords_to_index(wrapped.0, wrapped.1)
+}
+
 /// Trait for `HexMap` renderers
 pub trait Renderer {
     type Output;
@@ -58,9 +95,26 @@ pub trait Renderer {
     /// Set scale of rendered hexagons
     fn set_scale(&mut self, scale: f32);
 
+    /// Renders `HexMap` and returns error instead of panicking
+    ///
+    /// Renderers which can fail during rendering override this function
+    fn try_render(&self, map: &HexMap) -> Result<Self::Output, RenderError> {
+        Ok(self.render(map))
+    }
+
     /// Returns image generated from tiles
+    ///
+    /// # Panics
+    /// when tiles have incorrect size or count
     fn tiles_to_image(tiles: &[Vec<u8>], map: &HexMap, multiplier: f32, tile_size: usize) -> Image
         where Self: Sized
+    {
+        Self::try_tiles_to_image(tiles, map, multiplier, tile_size).unwrap()
+    }
+
+    /// Returns image generated from tiles, or error when tiles have incorrect size or count
+    fn try_tiles_to_image(tiles: &[Vec<u8>], map: &HexMap, multiplier: f32, tile_size: usize) -> Result<Image, RenderError>
+        where Self: Sized
     {
         const CHANNELS: usize = 4;
 
@@ -68,7 +122,7 @@ pub trait Renderer {
         let buf_size = tile_size * tile_size * CHANNELS;
         for (tile_num, tile) in tiles.iter().enumerate() {
             if tile.len() != buf_size {
-                panic!("tile #{} has incorrect size, got: {}, expected: {}", tile_num, tile.len(), buf_size);
+                return Err(RenderError::TileSize{tile: tile_num, size: tile.len(), expected: buf_size});
             }
         }
 
@@ -79,7 +133,7 @@ pub trait Renderer {
 
         // check correct number of tiles
         if tiles_x * tiles_y != tiles.len() {
-            panic!("incorrect number of tiles, got: {}, expected: {}", tiles.len(), tiles_x * tiles_y);
+            return Err(RenderError::TileCount{count: tiles.len(), expected: tiles_x * tiles_y});
         }
 
 
@@ -104,19 +158,30 @@ pub trait Renderer {
             }
         }
 
-        Image::from_buffer(target_size_x as u32, target_size_y as u32, buffer, ColorMode::Rgba)
+        Ok(Image::from_buffer(target_size_x as u32, target_size_y as u32, buffer, ColorMode::Rgba))
     }
 
     /// Adds tile to image buffer
+    ///
+    /// # Panics
+    /// when tile has incorrect size
     fn add_tile_to_image(tile: &[u8], image_buffer: &mut[u8], map: &HexMap, multiplier: f32, tile_size: usize, tile_x: usize, tile_y: usize)
         where Self: Sized
+    {
+        Self::try_add_tile_to_image(tile, image_buffer, map, multiplier, tile_size, tile_x, tile_y).unwrap()
+    }
+
+    /// Adds tile to image buffer, or returns error when tile has incorrect size
+    fn try_add_tile_to_image(tile: &[u8], image_buffer: &mut[u8], map: &HexMap, multiplier: f32, tile_size: usize, tile_x: usize, tile_y: usize) -> Result<(), RenderError>
+        where Self: Sized
     {
         const CHANNELS: usize = 4;
 
         //check if tile has correct size
         let buf_size = tile_size * tile_size * CHANNELS;
         if tile.len() != buf_size {
-            panic!("tile has incorrect size, got: {}, expected: {}", tile.len(), buf_size);
+            let tiles_x = ((map.absolute_size_x * multiplier) / tile_size as f32).ceil() as usize;
+            return Err(RenderError::TileSize{tile: tile_y * tiles_x + tile_x, size: tile.len(), expected: buf_size});
         }
 
         let target_size_x = (map.absolute_size_x * multiplier) as usize;
@@ -140,6 +205,7 @@ pub trait Renderer {
 
             slice.copy_from_slice(&tile[(tile_slice_start)..(tile_slice_start + end - start)]);
         }
+        Ok(())
     }
 
     /// Should the map repeat on the X axis
@@ -6,7 +6,9 @@ use num_cpus;
 
 use enigmap::{HexMap, Hex, HexType};
 
-use crate::renderers::{Image, Renderer, ColorMode, get_hex_vertex};
+use crate::renderers::{Image, Renderer, RenderError, ColorMode, Blend, DecorStyle, Labels, Legend, Outline, Relief, get_hex_vertex};
+use crate::renderers::decor::Polygon;
+use crate::renderers::labels::text_polygons;
 use crate::renderers::colors::ColorMap;
 
 /// Software renderer
@@ -21,8 +23,22 @@ pub struct Basic {
     randomize_colors: bool,
     /// Use anti-aliasing when rendering
     antialiasing: bool,
+    /// Render `Hex::decor`
+    render_decor: bool,
     /// Colormap used when rendering
-    pub colors: ColorMap
+    pub colors: ColorMap,
+    /// Style of rendered decor
+    pub decor_style: DecorStyle,
+    /// Style of hex outlines
+    pub outline: Outline,
+    /// Coordinate and user labels
+    pub labels: Labels,
+    /// Legend panel appended under the map
+    pub legend: Legend,
+    /// Hillshading from elevation
+    pub relief: Relief,
+    /// Smooth transitions between hexes
+    pub blend: Blend
 }
 
 impl Basic {
@@ -340,6 +356,77 @@ impl Basic {
         };
     }
 
+    /// Returns outlines, decor and labels drawn over hexes
+    fn overlay_shapes(&self, map: &HexMap) -> Vec<(Polygon, [u8;3])> {
+        let mut shapes = self.outline.polygons(map, self.wrap_map);
+        if self.render_decor {
+            shapes.extend(self.decor_style.polygons(map, self.wrap_map));
+        }
+        shapes.extend(self.labels.polygons(map));
+        shapes
+    }
+
+    fn render_shapes_to_image(&self, map: &HexMap, shapes: &[(Polygon, [u8;3])], image: &mut Image, pixel_offset: (f32, f32)) {
+        // `render_polygon` samples pixel centers, move shapes instead
+        let shift = (0.5 - pixel_offset.0, 0.5 - pixel_offset.1);
+        let wrap_offset = map.size_x as f32 * self.multiplier;
+        for (polygon, color) in shapes {
+            let mut points = polygon.iter().map(|point| (point.0 * self.multiplier + shift.0, point.1 * self.multiplier + shift.1)).collect::<Vec<_>>();
+            self.render_polygon(&points, image, *color);
+
+            if !self.wrap_map {
+                continue;
+            }
+            let min_x = points.iter().fold(f32::MAX, |min, point| min.min(point.0));
+            let max_x = points.iter().fold(f32::MIN, |max, point| max.max(point.0));
+            let offset = if min_x < 0.0 {
+                wrap_offset
+            } else if max_x > image.width() as f32 {
+                -wrap_offset
+            } else {
+                continue
+            };
+            for point in &mut points {
+                point.0 += offset;
+            }
+            self.render_polygon(&points, image, *color);
+        }
+    }
+
+    /// Returns image extended by legend panel, if the legend is enabled
+    fn append_legend(&self, map: &HexMap, image: Image) -> Image {
+        if !self.legend.is_enabled() {
+            return image;
+        }
+        let layout = self.legend.layout(map, &self.colors, image.width() as f32 / self.multiplier);
+        let mut shapes = layout.rectangles;
+        for text in &layout.texts {
+            shapes.extend(text_polygons(text, self.legend.text_color));
+        }
+
+        let panel_height = (layout.height * self.multiplier).ceil() as u32;
+        let render_panel = |pixel_offset: (f32, f32)| {
+            let mut panel = Image::from_fn(image.width(), panel_height, |_, _| self.legend.background);
+            // `render_polygon` samples pixel centers, move shapes instead
+            let shift = (0.5 - pixel_offset.0, 0.5 - pixel_offset.1);
+            for (polygon, color) in &shapes {
+                let points = polygon.iter().map(|point| (point.0 * self.multiplier + shift.0, point.1 * self.multiplier + shift.1)).collect::<Vec<_>>();
+                self.render_polygon(&points, &mut panel, *color);
+            }
+            panel
+        };
+        let panel = if self.antialiasing {
+            let offsets = [(0.25, 0.25), (0.75, 0.25), (0.75, 0.75), (0.25, 0.75)];
+            Self::reconstruct_image(offsets.iter().map(|&offset| render_panel(offset)).collect())
+        } else {
+            render_panel((0.5, 0.5))
+        };
+
+        let mut buffer = image.buffer().to_vec();
+        buffer.extend_from_slice(panel.buffer());
+        Image::from_buffer(image.width(), image.height() + panel_height, buffer, ColorMode::Rgb)
+    }
+
     fn render_aa_image(&self, map: &HexMap) -> Image {
         let width = (map.absolute_size_x * self.multiplier) as u32;
         let height = (map.absolute_size_y * self.multiplier) as u32;
@@ -360,6 +447,7 @@ impl Basic {
         let shared_renderer = Arc::new(self.to_owned());
         let shared_wrappings = Arc::new(wrappings);
         let shared_colors = Arc::new(colors);
+        let shared_shapes = Arc::new(self.overlay_shapes(map));
 
         let offsets = [(0.25, 0.25), (0.75, 0.25), (0.75, 0.75), (0.25, 0.75)];
         let images = thread::scope(|s| {
@@ -369,6 +457,7 @@ impl Basic {
                 let shared_renderer = Arc::clone(&shared_renderer);
                 let shared_wrappings = Arc::clone(&shared_wrappings);
                 let shared_colors = Arc::clone(&shared_colors);
+                let shared_shapes = Arc::clone(&shared_shapes);
                 let mut image = Image::new(width, height, ColorMode::Rgb);
                 images.push(s.spawn(move |_| {
                     let mut settings = HexRenderSettings{
@@ -378,15 +467,20 @@ impl Basic {
                         is_bottom_row: false,
                         pixel_offset: offsets[i]
                     };
-                    for (index, hex) in shared_field.iter().enumerate() {
-                        settings.color = shared_colors[index];
-                        settings.wrapping = shared_wrappings[index];
-                        // check bottom row
-                        if hex.y as u32 == map.size_y - 1 {
-                            settings.is_bottom_row = true;
+                    if shared_renderer.blend.is_enabled() {
+                        shared_renderer.blend.render_to_image(map, &shared_colors, &mut image, shared_renderer.multiplier, shared_renderer.wrap_map, offsets[i]);
+                    } else {
+                        for (index, hex) in shared_field.iter().enumerate() {
+                            settings.color = shared_colors[index];
+                            settings.wrapping = shared_wrappings[index];
+                            // check bottom row
+                            if hex.y as u32 == map.size_y - 1 {
+                                settings.is_bottom_row = true;
+                            }
+                            shared_renderer.render_hex(&mut image, hex, &settings);
                         }
-                        shared_renderer.render_hex(&mut image, hex, &settings);
                     }
+                    shared_renderer.render_shapes_to_image(map, &shared_shapes, &mut image, offsets[i]);
                     image
                 }));
             }
@@ -434,8 +528,9 @@ impl Basic {
         };
 
         let mut colors = Vec::with_capacity(map.get_area() as usize);
+        let shades = self.relief.shades(map, self.wrap_map);
 
-        for hex in &map.field {
+        for (index, hex) in map.field.iter().enumerate() {
             let color_diff = rng.gen_range(0.98, 1.02);
 
             let mut color = match hex.terrain_type {
@@ -459,6 +554,15 @@ impl Basic {
                     }
                 }
             }
+
+            // shade by relief, without elevation all hexes are flat
+            if let Some(shades) = &shades {
+                if !matches!(hex.terrain_type, HexType::Debug(_, _, _)) {
+                    for color_channel in &mut color {
+                        *color_channel = clamp_color(f32::from(*color_channel) * shades[index]);
+                    }
+                }
+            }
             colors.push(color);
         }
 
@@ -472,11 +576,16 @@ impl Basic {
     pub fn use_antialiasing(&mut self, value: bool) {
         self.antialiasing = value;
     }
+
+    /// Should `Hex::decor` be rendered with `decor_style`
+    pub fn set_render_decor(&mut self, value: bool) {
+        self.render_decor = value;
+    }
 }
 
 impl Default for Basic {
     fn default() -> Basic {
-        Basic{multiplier: 50.0, wrap_map: true, randomize_colors: true, antialiasing: true, colors: ColorMap::new()}
+        Basic{multiplier: 50.0, wrap_map: true, randomize_colors: true, antialiasing: true, render_decor: true, colors: ColorMap::new(), decor_style: DecorStyle::default(), outline: Outline::default(), labels: Labels::default(), legend: Legend::default(), relief: Relief::default(), blend: Blend::default()}
     }
 }
 
@@ -485,7 +594,7 @@ impl Renderer for Basic {
 
     fn render(&self, map: &HexMap) -> Image {
         if self.antialiasing {
-            return self.render_aa_image(map);
+            return self.append_legend(map, self.render_aa_image(map));
         }
 
         let width = (map.absolute_size_x * self.multiplier) as u32;
@@ -502,21 +611,33 @@ impl Renderer for Basic {
             pixel_offset: (0.5, 0.5)
         };
 
-        for (index, hex) in map.field.iter().enumerate() {
-            settings.wrapping = if self.wrap_map && index as u32 % map.size_x == 0 {
-                RenderWrapped::Right
-            } else if self.wrap_map && index as u32 % map.size_x == (map.size_x - 1) {
-                RenderWrapped::Left
-            } else {
-                RenderWrapped::None
-            };
-            // check bottom row
-            if hex.y as u32 == map.size_y - 1 {
-                settings.is_bottom_row = true;
+        if self.blend.is_enabled() {
+            self.blend.render_to_image(map, &colors, &mut image, self.multiplier, self.wrap_map, settings.pixel_offset);
+        } else {
+            for (index, hex) in map.field.iter().enumerate() {
+                settings.color = colors[index];
+                settings.wrapping = if self.wrap_map && index as u32 % map.size_x == 0 {
+                    RenderWrapped::Right
+                } else if self.wrap_map && index as u32 % map.size_x == (map.size_x - 1) {
+                    RenderWrapped::Left
+                } else {
+                    RenderWrapped::None
+                };
+                // check bottom row
+                if hex.y as u32 == map.size_y - 1 {
+                    settings.is_bottom_row = true;
+                }
+                self.render_hex(&mut image, hex, &settings);
             }
-            self.render_hex(&mut image, hex, &settings);
         }
-        image
+        self.render_shapes_to_image(map, &self.overlay_shapes(map), &mut image, settings.pixel_offset);
+        self.append_legend(map, image)
+    }
+
+    fn try_render(&self, map: &HexMap) -> Result<Image, RenderError> {
+        self.colors.validate(map)?;
+        self.relief.validate(map)?;
+        Ok(self.render(map))
     }
 
     fn set_scale(&mut self, scale: f32) {
@@ -0,0 +1,160 @@
+use noise::{Perlin, NoiseFn, Seedable};
+
+use enigmap::{HexMap, Hex, RATIO};
+
+use crate::renderers::{Image, EDGE_DIRECTIONS, get_hex_index};
+
+/// Smooth blending of colors between neighbouring hexes
+///
+/// Every pixel mixes colors of the closest hex and its neighbours, weighted by distance of their centers.
+/// Sizes are relative to the width of `Hex`.
+/// ## Usage
+/// ```
+/// use enigmap_renderer::renderers::{Basic, Blend};
+///
+/// let mut renderer = Basic::default();
+/// renderer.blend = Blend{falloff: 0.15, noise_amplitude: 0.2, ..Default::default()};
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Blend {
+    /// Width of transition between hexes, `0.0` keeps hard hex edges
+    pub falloff: f32,
+    /// Maximal shift of sampled position by noise, makes hex boundaries irregular
+    pub noise_amplitude: f32,
+    /// Scale of boundary noise
+    pub noise_scale: f64,
+    /// Seed of boundary noise
+    pub seed: u32,
+}
+
+impl Blend {
+    /// Returns `true` if the map is not rendered with hard hex edges
+    pub fn is_enabled(&self) -> bool {
+        self.falloff > 0.0 || self.noise_amplitude > 0.0
+    }
+
+    /// Fills image with blended colors of hexes, sampled at `pixel_offset` inside of every pixel
+    ///
+    /// Pixels outside of the map are left untouched.
+    pub(crate) fn render_to_image(&self, map: &HexMap, colors: &[[u8; 3]], image: &mut Image, multiplier: f32, wrap_map: bool, pixel_offset: (f32, f32)) {
+        let noise = Perlin::new().set_seed(self.seed);
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                let mut point = ((x as f32 + pixel_offset.0) / multiplier, (y as f32 + pixel_offset.1) / multiplier);
+                if self.noise_amplitude > 0.0 {
+                    let noise_point = [f64::from(point.0) * self.noise_scale, f64::from(point.1) * self.noise_scale];
+                    point.0 += noise.get(noise_point) as f32 * self.noise_amplitude;
+                    point.1 += noise.get([noise_point[0] + 100.0, noise_point[1]]) as f32 * self.noise_amplitude;
+                }
+                if let Some(color) = self.sample(map, colors, point, wrap_map) {
+                    image.put_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// Returns color on given point in relative (non-multiplied) coordinates, `None` outside of the map
+    fn sample(&self, map: &HexMap, colors: &[[u8; 3]], point: (f32, f32), wrap_map: bool) -> Option<[u8; 3]> {
+        let (closest, closest_distance) = closest_hex(map, point, wrap_map)?;
+        let index = get_hex_index(map, closest.x, closest.y, wrap_map)?;
+        if self.falloff <= 0.0 {
+            return Some(colors[index]);
+        }
+
+        let mut total = [0.0; 3];
+        let mut weights = 0.0;
+        let mut add = |index: usize, distance: f32| {
+            let weight = (-(distance - closest_distance) / self.falloff).exp();
+            for (channel, value) in total.iter_mut().zip(&colors[index]) {
+                *channel += f32::from(*value) * weight;
+            }
+            weights += weight;
+        };
+        add(index, closest_distance);
+        for &(dx, dy) in &EDGE_DIRECTIONS {
+            let neighbour = Hex::from_coords(closest.x + dx, closest.y + dy);
+            if let Some(index) = get_hex_index(map, neighbour.x, neighbour.y, wrap_map) {
+                add(index, distance(neighbour.center(), point));
+            }
+        }
+        Some([(total[0] / weights) as u8, (total[1] / weights) as u8, (total[2] / weights) as u8])
+    }
+}
+
+impl Default for Blend {
+    fn default() -> Blend {
+        Blend{falloff: 0.0, noise_amplitude: 0.0, noise_scale: 2.0, seed: 0}
+    }
+}
+
+/// Returns hex, which contains the point, and distance to its center
+///
+/// Coordinates of returned hex may be outside of the map when it's wrapped
+pub(crate) fn closest_hex(map: &HexMap, point: (f32, f32), wrap_map: bool) -> Option<(Hex, f32)> {
+    let row = ((point.1 - RATIO / 2.0) / (RATIO * 0.75)).floor() as i32;
+    let mut closest: Option<(Hex, f32)> = None;
+    for y in row.max(0)..=(row + 1).min(map.size_y as i32 - 1) {
+        // column of the hex in offset coordinates
+        let column = (point.0 - 0.5 - (y % 2) as f32 * 0.5).round() as i32;
+        for x in (column - 1)..=(column + 1) {
+            let hex = Hex::from_coords(x - y / 2, y);
+            if get_hex_index(map, hex.x, hex.y, wrap_map).is_none() {
+                continue;
+            }
+            let distance = distance(hex.center(), point);
+            if closest.as_ref().map_or(true, |closest| distance < closest.1) {
+                closest = Some((hex, distance));
+            }
+        }
+    }
+    closest
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderers::ColorMode;
+
+    #[test]
+    fn blends_neighbours() {
+        let mut map = HexMap::new(10, 10);
+        map.field[34].terrain_type = enigmap::HexType::Field;
+        let colors = map.field.iter().map(|hex| if hex.terrain_type == enigmap::HexType::Field { [200, 0, 0] } else { [0, 0, 200] }).collect::<Vec<_>>();
+
+        // closest hex contains the point
+        for hex in &map.field {
+            let (x, y) = hex.center();
+            let (closest, _) = closest_hex(&map, (x + 0.3, y - 0.2), true).unwrap();
+            assert_eq!((hex.x, hex.y), Hex::unwrap_coords(closest.x, closest.y, map.size_x));
+        }
+
+        let (x, y) = map.field[34].center();
+        let hard = Blend::default();
+        assert_eq!(Some([200, 0, 0]), hard.sample(&map, &colors, (x + 0.45, y), true));
+        let soft = Blend{falloff: 0.2, ..Default::default()};
+        let edge = soft.sample(&map, &colors, (x + 0.45, y), true).unwrap();
+        assert!(edge[0] > 0 && edge[2] > 0 && edge[0] > edge[2]);
+        assert!(soft.sample(&map, &colors, (x, y), true).unwrap()[0] > edge[0]);
+
+        // noise moves boundaries
+        let mut smooth = Image::new(60, 60, ColorMode::Rgb);
+        soft.render_to_image(&map, &colors, &mut smooth, 10.0, true, (0.5, 0.5));
+        let mut noisy = Image::new(60, 60, ColorMode::Rgb);
+        Blend{noise_amplitude: 0.2, ..soft}.render_to_image(&map, &colors, &mut noisy, 10.0, true, (0.5, 0.5));
+        assert_ne!(smooth.buffer(), noisy.buffer());
+    }
+
+    #[test]
+    fn keeps_background_outside() {
+        let map = HexMap::new(10, 10);
+        let colors = vec![[0, 0, 200]; map.field.len()];
+        let mut image = Image::from_fn(130, 100, |_, _| [10, 10, 10]);
+        Blend{falloff: 0.2, ..Default::default()}.render_to_image(&map, &colors, &mut image, 10.0, false, (0.5, 0.5));
+        assert_eq!(&[0, 0, 200], image.get_pixel(50, 50));
+        assert_eq!(&[10, 10, 10], image.get_pixel(125, 50));
+    }
+}
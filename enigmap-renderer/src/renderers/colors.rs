@@ -1,5 +1,7 @@
 use std::collections::HashMap;
-use enigmap::HexType;
+use enigmap::{HexMap, HexType};
+
+use crate::renderers::RenderError;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -41,13 +43,36 @@ impl ColorMap {
         cm
     }
 
+    /// # Panics
+    /// when there is no color for the `HexType`
     pub fn get_color_f32(&self, ht: &HexType) -> &Color {
-        self.map.get(ht).unwrap()
+        self.try_get_color_f32(ht).unwrap()
     }
 
+    /// # Panics
+    /// when there is no color for the `HexType`
     pub fn get_color_u8(&self, ht: &HexType) -> (u8, u8, u8) {
-        let color = self.map.get(ht).unwrap();
-        ((color.r * 255.0) as u8, (color.g * 255.0) as u8, (color.b * 255.0) as u8)
+        self.try_get_color_u8(ht).unwrap()
+    }
+
+    pub fn try_get_color_f32(&self, ht: &HexType) -> Result<&Color, RenderError> {
+        self.map.get(ht).ok_or(RenderError::MissingColor(*ht))
+    }
+
+    pub fn try_get_color_u8(&self, ht: &HexType) -> Result<(u8, u8, u8), RenderError> {
+        let color = self.try_get_color_f32(ht)?;
+        Ok(((color.r * 255.0) as u8, (color.g * 255.0) as u8, (color.b * 255.0) as u8))
+    }
+
+    /// Checks that every `HexType` in the map has a color, `Debug` hexes carry their own color
+    pub fn validate(&self, map: &HexMap) -> Result<(), RenderError> {
+        for hex in &map.field {
+            if let HexType::Debug(..) = hex.terrain_type {
+                continue;
+            }
+            self.try_get_color_f32(&hex.terrain_type)?;
+        }
+        Ok(())
     }
 
     pub fn set_color_u8(&mut self, ht: HexType, color: (u8, u8, u8)) {
@@ -58,10 +83,32 @@ impl ColorMap {
     pub fn set_color_f32(&mut self, ht: HexType, color: (f32, f32, f32)) {
         self.map.insert(ht, Color::from_tupple(color));
     }
+
+    /// Returns iterator over all `HexType` and color pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&HexType, &Color)> {
+        self.map.iter()
+    }
 }
 
 impl Default for ColorMap {
     fn default() -> ColorMap {
         ColorMap::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_color() {
+        let mut map = HexMap::new(5, 5);
+        map.field[3].terrain_type = HexType::Debug(1, 2, 3);
+        assert_eq!(Ok(()), ColorMap::new().validate(&map));
+
+        let mut colors = ColorMap{map: HashMap::new()};
+        colors.set_color_u8(HexType::Ocean, (1, 2, 3));
+        assert_eq!(Err(RenderError::MissingColor(map.field[0].terrain_type)), colors.validate(&map));
+        assert_eq!(Ok((1, 2, 3)), colors.try_get_color_u8(&HexType::Ocean));
+    }
+}
@@ -0,0 +1,267 @@
+use enigmap::{HexMap, Hex, HexType, Decor};
+
+use crate::renderers::get_hex_index;
+
+/// Neighbour offsets in clockwise order
+const DIRECTIONS: [(i32, i32); 6] = [(0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1), (1, 0)];
+
+/// Number of vertices of circles
+const CIRCLE_VERTICES: usize = 8;
+
+/// Settlement and ruin icons in drawing order
+pub(crate) const SETTLEMENTS: [Decor; 3] = [Decor::RUIN, Decor::VILLAGE, Decor::CITY];
+
+/// Points of polygon in relative (non-multiplied) coordinates
+pub(crate) type Polygon = Vec<(f32, f32)>;
+
+/// Style of rendered `Decor`
+///
+/// Sizes are relative to the width of `Hex`.
+/// ## Usage
+/// ```
+/// use enigmap_renderer::renderers::{Basic, DecorStyle};
+///
+/// let mut renderer = Basic::default();
+/// renderer.decor_style = DecorStyle{river_width: 0.2, show_roads: false, ..Default::default()};
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DecorStyle {
+    pub river_color: [u8; 3],
+    pub river_width: f32,
+    pub road_color: [u8; 3],
+    pub road_width: f32,
+    pub city_color: [u8; 3],
+    pub village_color: [u8; 3],
+    pub ruin_color: [u8; 3],
+    pub hill_color: [u8; 3],
+    /// Size of settlement, ruin and hill icons
+    pub icon_size: f32,
+    pub show_rivers: bool,
+    pub show_roads: bool,
+    /// Show `CITY`, `VILLAGE` and `RUIN` icons
+    pub show_settlements: bool,
+    pub show_hills: bool,
+}
+
+impl DecorStyle {
+    /// Returns polygons of all decor on the map in drawing order, with their colors
+    ///
+    /// Polygons are in counter clockwise order
+    pub(crate) fn polygons(&self, map: &HexMap, wrap_map: bool) -> Vec<(Polygon, [u8; 3])> {
+        let mut polygons = Vec::new();
+
+        if self.show_hills {
+            for hex in map.field.iter().filter(|hex| hex.decor.contains(Decor::HILL)) {
+                let (x, y) = hex.center();
+                let (parts, color) = self.icon(Decor::HILL, (x, y + self.hill_offset(hex)));
+                polygons.extend(parts.into_iter().map(|part| (part, color)));
+            }
+        }
+
+        let lines = [(self.show_rivers, Decor::RIVER, self.river_width, self.river_color), (self.show_roads, Decor::ROAD, self.road_width, self.road_color)];
+        for &(show, flag, width, color) in &lines {
+            if !show {
+                continue;
+            }
+            for hex in map.field.iter().filter(|hex| hex.decor.contains(flag)) {
+                polygons.push((circle(hex.center(), width / 2.0), color));
+                for (from, to) in line_segments(map, hex, flag, wrap_map) {
+                    polygons.push((segment(from, to, width), color));
+                }
+            }
+        }
+
+        if self.show_settlements {
+            for &flag in &SETTLEMENTS {
+                for hex in map.field.iter().filter(|hex| hex.decor.contains(flag)) {
+                    let (parts, color) = self.icon(flag, hex.center());
+                    polygons.extend(parts.into_iter().map(|part| (part, color)));
+                }
+            }
+        }
+
+        for (points, _) in &mut polygons {
+            orient(points);
+        }
+        polygons
+    }
+
+    /// Returns shapes of `HILL`, `RUIN`, `VILLAGE` or `CITY` icon centered on `center` and its color
+    /// # Panics
+    /// when `flag` is not one of the icons
+    pub(crate) fn icon(&self, flag: Decor, center: (f32, f32)) -> (Vec<Polygon>, [u8; 3]) {
+        let size = self.icon_size / 2.0;
+        match flag {
+            Decor::HILL => (vec![hill(center, size)], self.hill_color),
+            Decor::RUIN => (ruin(center, size * 0.8), self.ruin_color),
+            Decor::VILLAGE => (house(center, size * 0.7), self.village_color),
+            Decor::CITY => (house(center, size), self.city_color),
+            _ => panic!("decor has no icon")
+        }
+    }
+
+    /// Returns vertical offset of hill, so it does not cover settlements
+    pub(crate) fn hill_offset(&self, hex: &Hex) -> f32 {
+        if hex.decor.intersects(Decor::CITY | Decor::VILLAGE | Decor::RUIN) {
+            self.icon_size * 0.3
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for DecorStyle {
+    fn default() -> DecorStyle {
+        DecorStyle{
+            river_color: [54, 108, 194],
+            river_width: 0.12,
+            road_color: [140, 104, 70],
+            road_width: 0.08,
+            city_color: [190, 40, 40],
+            village_color: [220, 170, 60],
+            ruin_color: [110, 110, 110],
+            hill_color: [120, 96, 64],
+            icon_size: 0.5,
+            show_rivers: true,
+            show_roads: true,
+            show_settlements: true,
+            show_hills: true
+        }
+    }
+}
+
+/// Returns segments from the hex center to the edges shared with connected neighbours
+///
+/// Hexes are connected when both have the `flag`, rivers also flow into `Water` and `Ocean`.
+/// Segments across the wrapped map edge end outside of the map.
+pub(crate) fn line_segments(map: &HexMap, hex: &Hex, flag: Decor, wrap_map: bool) -> Vec<((f32, f32), (f32, f32))> {
+    let center = hex.center();
+    let mut segments = Vec::new();
+    for &(dx, dy) in &DIRECTIONS {
+        let (x, y) = (hex.x + dx, hex.y + dy);
+        let other = match get_hex_index(map, x, y, wrap_map) {
+            Some(index) => &map.field[index],
+            None => continue
+        };
+        let river_mouth = flag == Decor::RIVER && matches!(other.terrain_type, HexType::Water | HexType::Ocean);
+        if other.decor.contains(flag) || river_mouth {
+            let other_center = Hex::from_coords(x, y).center();
+            segments.push((center, ((center.0 + other_center.0) / 2.0, (center.1 + other_center.1) / 2.0)));
+        }
+    }
+    segments
+}
+
+/// Returns rectangle of given width around line from `from` to `to`
+fn segment(from: (f32, f32), to: (f32, f32), width: f32) -> Polygon {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+    let normal = (-dy / length * width / 2.0, dx / length * width / 2.0);
+    vec![
+        (from.0 + normal.0, from.1 + normal.1),
+        (to.0 + normal.0, to.1 + normal.1),
+        (to.0 - normal.0, to.1 - normal.1),
+        (from.0 - normal.0, from.1 - normal.1)
+    ]
+}
+
+fn circle(center: (f32, f32), radius: f32) -> Polygon {
+    (0..CIRCLE_VERTICES).map(|index| {
+        let angle = index as f32 / CIRCLE_VERTICES as f32 * std::f32::consts::PI * 2.0;
+        (center.0 + angle.cos() * radius, center.1 + angle.sin() * radius)
+    }).collect()
+}
+
+/// Returns hill, `size` is half of its width
+fn hill(center: (f32, f32), size: f32) -> Polygon {
+    let (x, y) = center;
+    vec![(x - size, y + size * 0.5), (x, y - size * 0.5), (x + size, y + size * 0.5)]
+}
+
+/// Returns house with roof, `size` is half of its width
+fn house(center: (f32, f32), size: f32) -> Vec<Polygon> {
+    let (x, y) = center;
+    vec![
+        vec![(x - size * 0.7, y - size * 0.1), (x + size * 0.7, y - size * 0.1), (x + size * 0.7, y + size * 0.7), (x - size * 0.7, y + size * 0.7)],
+        vec![(x - size, y), (x, y - size * 0.8), (x + size, y)]
+    ]
+}
+
+/// Returns two broken pillars, `size` is half of their width
+fn ruin(center: (f32, f32), size: f32) -> Vec<Polygon> {
+    let (x, y) = center;
+    vec![
+        vec![(x - size * 0.8, y - size * 0.6), (x - size * 0.3, y - size * 0.8), (x - size * 0.3, y + size * 0.7), (x - size * 0.8, y + size * 0.7)],
+        vec![(x + size * 0.3, y - size * 0.1), (x + size * 0.8, y + size * 0.1), (x + size * 0.8, y + size * 0.7), (x + size * 0.3, y + size * 0.7)]
+    ]
+}
+
+/// Reverses polygon in clockwise order, `Basic::render_polygon` needs counter clockwise order
+pub(crate) fn orient(points: &mut [(f32, f32)]) {
+    let mut area = 0.0;
+    for index in 0..points.len() {
+        let (a, b) = (points[index], points[(index + 1) % points.len()]);
+        area += a.0 * b.1 - b.0 * a.1;
+    }
+    // y axis points down
+    if area > 0.0 {
+        points.reverse();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connects_neighbours() {
+        let mut map = HexMap::new(10, 10);
+        map.fill(HexType::Field);
+        for &(x, y) in &[(2, 4), (3, 4), (8, 2), (-1, 2)] {
+            let index = map.coords_to_index(x, y).unwrap();
+            map.field[index].decor.insert(Decor::ROAD);
+        }
+
+        let index = map.coords_to_index(2, 4).unwrap();
+        let segments = line_segments(&map, &map.field[index], Decor::ROAD, true);
+        assert_eq!(1, segments.len());
+        let (from, to) = segments[0];
+        assert_eq!(map.field[index].center(), from);
+        assert!((to.0 - from.0 - 0.5).abs() < 0.001);
+
+        // connected over wrapped edge only when wrapping
+        let index = map.coords_to_index(8, 2).unwrap();
+        assert_eq!(1, line_segments(&map, &map.field[index], Decor::ROAD, true).len());
+        assert_eq!(0, line_segments(&map, &map.field[index], Decor::ROAD, false).len());
+    }
+
+    #[test]
+    fn renders_decor() {
+        use crate::renderers::{Basic, Renderer};
+
+        let mut map = HexMap::new(10, 10);
+        map.fill(HexType::Field);
+        let city = map.coords_to_index(2, 4).unwrap();
+        let river = map.coords_to_index(5, 4).unwrap();
+        map.field[city].decor.insert(Decor::CITY);
+        map.field[river].decor.insert(Decor::RIVER);
+        map.field[river + 1].decor.insert(Decor::RIVER);
+
+        let mut renderer = Basic::default();
+        renderer.set_scale(20.0);
+        renderer.set_random_colors(false);
+        renderer.use_antialiasing(false);
+        let pixel = |image: &crate::renderers::Image, point: (f32, f32)| image.get_pixel((point.0 * 20.0) as u32, (point.1 * 20.0) as u32).to_vec();
+
+        let image = renderer.render(&map);
+        let style = DecorStyle::default();
+        let (x, y) = map.field[city].center();
+        assert_eq!(style.city_color.to_vec(), pixel(&image, (x, y + 0.1)));
+        let (x, y) = map.field[river].center();
+        assert_eq!(style.river_color.to_vec(), pixel(&image, (x + 0.5, y)));
+
+        renderer.set_render_decor(false);
+        let image = renderer.render(&map);
+        assert_ne!(style.river_color.to_vec(), pixel(&image, (x + 0.5, y)));
+    }
+}
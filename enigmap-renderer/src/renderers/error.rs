@@ -0,0 +1,81 @@
+use std::error::Error;
+use std::fmt;
+
+use enigmap::HexType;
+
+/// Error returned by fallible renderer construction and rendering
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderError {
+    /// OpenGL context could not be created, e.g., on machines without GPU
+    Context(String),
+    /// OpenGL failed while creating shaders, buffers or while drawing
+    OpenGl(String),
+    /// `ColorMap` has no color for `HexType` present in the map
+    MissingColor(HexType),
+    /// Texture file could not be decoded
+    Texture{path: String, message: String},
+    /// Tile buffer has different length than expected
+    TileSize{tile: usize, size: usize, expected: usize},
+    /// Number of tiles does not cover the map
+    TileCount{count: usize, expected: usize},
+    /// Relief elevation has different length than `HexMap::field`
+    Elevation{size: usize, expected: usize},
+}
+
+impl RenderError {
+    /// Wraps OpenGL error
+    pub(crate) fn open_gl<E: fmt::Display>(error: E) -> RenderError {
+        RenderError::OpenGl(error.to_string())
+    }
+
+    /// Error of OpenGL without support of instanced rendering
+    pub(crate) fn instancing() -> RenderError {
+        RenderError::OpenGl(String::from("instancing is not supported"))
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::Context(message) => write!(f, "could not create OpenGL context: {}", message),
+            RenderError::OpenGl(message) => write!(f, "OpenGL error: {}", message),
+            RenderError::MissingColor(hex_type) => write!(f, "color map has no color for {}", String::from(*hex_type)),
+            RenderError::Texture{path, message} => write!(f, "could not load texture {}: {}", path, message),
+            RenderError::TileSize{tile, size, expected} => write!(f, "tile #{} has incorrect size, got: {}, expected: {}", tile, size, expected),
+            RenderError::TileCount{count, expected} => write!(f, "incorrect number of tiles, got: {}, expected: {}", count, expected),
+            RenderError::Elevation{size, expected} => write!(f, "elevation has different size than the map, got: {}, expected: {}", size, expected),
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enigmap::HexMap;
+    use crate::renderers::{Basic, OGL, Relief, Renderer, Sprite};
+
+    #[test]
+    fn errors_instead_of_panics() {
+        let map = HexMap::new(10, 10);
+        let mut renderer = Basic::default();
+        renderer.set_scale(20.0);
+        assert!(renderer.try_render(&map).is_ok());
+
+        renderer.relief = Relief::with_elevation(vec![0.0; 5]);
+        assert_eq!(Err(RenderError::Elevation{size: 5, expected: 100}), renderer.try_render(&map).map(|_| ()));
+
+        let tiles = vec![vec![0; 4 * 4 * 4]; 3];
+        let error = Basic::try_tiles_to_image(&tiles, &map, 2.0, 4).unwrap_err();
+        assert_eq!(RenderError::TileCount{count: 3, expected: 6 * 5}, error);
+        let error = Basic::try_tiles_to_image(&[vec![0; 10]], &map, 2.0, 4).unwrap_err();
+        assert_eq!("tile #0 has incorrect size, got: 10, expected: 64", error.to_string());
+
+        // machines without display return error instead of panicking
+        if cfg!(target_os = "linux") && std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+            assert!(matches!(OGL::try_new(), Err(RenderError::Context(_))));
+            assert!(matches!(Sprite::try_from_folder("./examples/textures"), Err(RenderError::Context(_))));
+        }
+    }
+}
@@ -106,8 +106,9 @@ impl Image {
 
     #[inline(always)]
     pub fn get_pixel(&self, x: u32, y: u32) -> &[u8] {
-        let index = ((x + y * self.width) * 3) as usize;
-        &self.buffer[index..index + 3]
+        let channels = self.color_mode as u32;
+        let index = ((x + y * self.width) * channels) as usize;
+        &self.buffer[index..index + channels as usize]
     }
 }
 
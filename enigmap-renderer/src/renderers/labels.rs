@@ -0,0 +1,200 @@
+use enigmap::{HexMap, Hex};
+
+use crate::renderers::decor::{Polygon, orient};
+use crate::renderers::font::{self, CELL_WIDTH, CELL_HEIGHT, GLYPH_WIDTH};
+
+/// Offsets of user label from its hex, tried in order until the label does not overlap others
+const LABEL_OFFSETS: [f32; 5] = [0.0, -1.2, 1.2, -2.4, 2.4];
+
+/// Vertical offset of coordinate labels from hex center
+const COORDINATE_OFFSET: f32 = -0.3;
+
+/// Maximal width of coordinate labels, relative to hex width
+const COORDINATE_WIDTH: f32 = 0.8;
+
+/// Format of hex coordinate labels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateFormat {
+    None,
+    /// Column and row of the hex
+    Offset,
+    /// Cube coordinates, which sum up to zero
+    Cube,
+}
+
+/// Text anchored to hex on given `Hex` coordinates
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub x: i32,
+    pub y: i32,
+    pub text: String,
+}
+
+impl Label {
+    pub fn new<T: Into<String>>(x: i32, y: i32, text: T) -> Label {
+        Label{x, y, text: text.into()}
+    }
+}
+
+/// Coordinate and user labels on rendered map
+///
+/// Sizes are heights of text relative to the width of `Hex`.
+/// User labels are moved up and down when they overlap, coordinate labels are not rendered under user labels.
+/// ## Usage
+/// ```
+/// use enigmap_renderer::renderers::{Basic, Labels, CoordinateFormat};
+///
+/// let mut renderer = Basic::default();
+/// renderer.labels = Labels{coordinates: CoordinateFormat::Offset, ..Default::default()};
+/// renderer.labels.add(10, 5, "Capital");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Labels {
+    pub coordinates: CoordinateFormat,
+    pub coordinate_size: f32,
+    pub labels: Vec<Label>,
+    pub label_size: f32,
+    pub color: [u8; 3],
+}
+
+/// Label with its final position
+#[derive(Debug, Clone)]
+pub(crate) struct PlacedLabel {
+    pub text: String,
+    /// Center of the text in relative (non-multiplied) coordinates
+    pub center: (f32, f32),
+    /// Height of the text
+    pub size: f32,
+}
+
+impl PlacedLabel {
+    fn width(&self) -> f32 {
+        text_width(&self.text, self.size)
+    }
+
+    fn overlaps(&self, other: &PlacedLabel) -> bool {
+        (self.center.0 - other.center.0).abs() * 2.0 < self.width() + other.width()
+            && (self.center.1 - other.center.1).abs() * 2.0 < self.size + other.size
+    }
+}
+
+impl Labels {
+    /// Adds label to hex on given coordinates
+    pub fn add<T: Into<String>>(&mut self, x: i32, y: i32, text: T) {
+        self.labels.push(Label::new(x, y, text));
+    }
+
+    /// Returns text of coordinate label of the hex
+    pub fn coordinate_text(&self, hex: &Hex) -> Option<String> {
+        match self.coordinates {
+            CoordinateFormat::None => None,
+            CoordinateFormat::Offset => Some(format!("{},{}", hex.x + hex.y / 2, hex.y)),
+            CoordinateFormat::Cube => Some(format!("{},{},{}", hex.x, hex.y, -hex.x - hex.y)),
+        }
+    }
+
+    /// Returns positions of all labels, overlapping labels are moved or removed
+    pub(crate) fn layout(&self, map: &HexMap) -> Vec<PlacedLabel> {
+        let mut placed: Vec<PlacedLabel> = Vec::new();
+
+        for label in &self.labels {
+            let (x, y) = Hex::from_coords(label.x, label.y).center();
+            let candidates = LABEL_OFFSETS.iter().map(|offset| PlacedLabel{text: label.text.clone(), center: (x, y + offset * self.label_size), size: self.label_size}).collect::<Vec<_>>();
+            // keep the label on its hex when there is no free space
+            let label = candidates.iter().find(|candidate| placed.iter().all(|other| !candidate.overlaps(other))).unwrap_or(&candidates[0]).clone();
+            placed.push(label);
+        }
+
+        let user_labels = placed.len();
+        for hex in &map.field {
+            let text = match self.coordinate_text(hex) {
+                Some(text) => text,
+                None => break
+            };
+            let (x, y) = hex.center();
+            let size = self.coordinate_size.min(COORDINATE_WIDTH / text_width(&text, 1.0));
+            let label = PlacedLabel{text, center: (x, y + COORDINATE_OFFSET), size};
+            if placed[..user_labels].iter().all(|other| !label.overlaps(other)) {
+                placed.push(label);
+            }
+        }
+        placed
+    }
+
+    /// Returns pixels of bitmap font as polygons with their color
+    pub(crate) fn polygons(&self, map: &HexMap) -> Vec<(Polygon, [u8; 3])> {
+        self.layout(map).iter().flat_map(|label| text_polygons(label, self.color)).collect()
+    }
+}
+
+impl Default for Labels {
+    fn default() -> Labels {
+        Labels{
+            coordinates: CoordinateFormat::None,
+            coordinate_size: 0.16,
+            labels: Vec::new(),
+            label_size: 0.3,
+            color: [20, 20, 20]
+        }
+    }
+}
+
+/// Returns pixels of label in bitmap font as polygons with their color
+pub(crate) fn text_polygons(label: &PlacedLabel, color: [u8; 3]) -> Vec<(Polygon, [u8; 3])> {
+    let mut polygons = Vec::new();
+    let pixel = label.size / CELL_HEIGHT as f32;
+    let left = label.center.0 - label.width() / 2.0;
+    let top = label.center.1 - label.size / 2.0;
+    for (index, character) in label.text.chars().enumerate() {
+        let cell_left = left + (index as u32 * CELL_WIDTH) as f32 * pixel;
+        for (row, bits) in font::glyph(character).iter().enumerate() {
+            let row_top = top + (row + 1) as f32 * pixel;
+            // join neighbouring pixels on row into one rectangle
+            let mut column = 0;
+            while column < GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    column += 1;
+                    continue;
+                }
+                let start = column;
+                while column < GLYPH_WIDTH && bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    column += 1;
+                }
+                let (x0, x1) = (cell_left + start as f32 * pixel, cell_left + column as f32 * pixel);
+                let mut rectangle = vec![(x0, row_top), (x1, row_top), (x1, row_top + pixel), (x0, row_top + pixel)];
+                orient(&mut rectangle);
+                polygons.push((rectangle, color));
+            }
+        }
+    }
+    polygons
+}
+
+/// Returns width of text with given height
+pub(crate) fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * CELL_WIDTH as f32 / CELL_HEIGHT as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_do_not_overlap() {
+        let map = HexMap::new(10, 10);
+        let mut labels = Labels{coordinates: CoordinateFormat::Cube, ..Default::default()};
+        labels.add(3, 4, "Capital");
+        labels.add(3, 4, "Port");
+
+        assert_eq!(Some("3,4,-7".to_owned()), labels.coordinate_text(&Hex::from_coords(3, 4)));
+        labels.coordinates = CoordinateFormat::Offset;
+        assert_eq!(Some("5,4".to_owned()), labels.coordinate_text(&Hex::from_coords(3, 4)));
+
+        let placed = labels.layout(&map);
+        assert_eq!(("Capital", "Port"), (placed[0].text.as_str(), placed[1].text.as_str()));
+        assert!(!placed[0].overlaps(&placed[1]));
+        // coordinate label of the labelled hex is hidden
+        assert_eq!(2 + 100 - 1, placed.len());
+        assert!(placed.iter().all(|label| label.text != "5,4"));
+    }
+}
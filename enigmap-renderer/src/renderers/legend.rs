@@ -0,0 +1,157 @@
+use enigmap::{HexMap, HexType};
+
+use crate::renderers::colors::ColorMap;
+use crate::renderers::decor::{Polygon, orient};
+use crate::renderers::labels::{PlacedLabel, text_width};
+
+/// Space around panel content, relative to the width of `Hex`
+const PADDING: f32 = 0.3;
+
+/// Legend panel with terrain types and scale bar appended under the map
+///
+/// Sizes are relative to the width of `Hex`.
+/// ## Usage
+/// ```
+/// use enigmap_renderer::renderers::{Basic, Legend};
+///
+/// let mut renderer = Basic::default();
+/// renderer.legend = Legend{show_terrain: true, scale_bar: Some(5), ..Default::default()};
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Legend {
+    /// List every `HexType` present in the map
+    pub show_terrain: bool,
+    /// Length of scale bar in hexes, `None` hides the scale bar
+    pub scale_bar: Option<u32>,
+    pub text_size: f32,
+    pub text_color: [u8; 3],
+    pub background: [u8; 3],
+}
+
+/// Content of legend panel, positions are relative to its top left corner
+pub(crate) struct LegendLayout {
+    pub height: f32,
+    pub rectangles: Vec<(Polygon, [u8; 3])>,
+    pub texts: Vec<PlacedLabel>,
+}
+
+impl Legend {
+    /// Returns `true` if legend panel has any content
+    pub fn is_enabled(&self) -> bool {
+        self.show_terrain || self.scale_bar.is_some()
+    }
+
+    /// Returns terrain types present in the map, in order of their index
+    pub fn terrain_types(map: &HexMap) -> Vec<HexType> {
+        let mut types: Vec<HexType> = Vec::new();
+        for hex in &map.field {
+            if !types.contains(&hex.terrain_type) {
+                types.push(hex.terrain_type);
+            }
+        }
+        types.sort_by_key(|&hex_type| match hex_type {
+            HexType::Debug(r, g, b) => (i32::from(hex_type), [r, g, b]),
+            _ => (i32::from(hex_type), [0, 0, 0])
+        });
+        types
+    }
+
+    /// Lays out panel content into rows of given width
+    pub(crate) fn layout(&self, map: &HexMap, colors: &ColorMap, width: f32) -> LegendLayout {
+        let mut rectangles = Vec::new();
+        let mut texts = Vec::new();
+        let swatch = self.text_size * 1.2;
+        let row_height = swatch + PADDING;
+        let mut cursor = (PADDING, PADDING);
+
+        if self.show_terrain {
+            for hex_type in Self::terrain_types(map) {
+                let name = String::from(hex_type);
+                let entry_width = swatch + self.text_size + text_width(&name, self.text_size);
+                if cursor.0 > PADDING && cursor.0 + entry_width > width - PADDING {
+                    cursor = (PADDING, cursor.1 + row_height);
+                }
+                let color = match hex_type {
+                    HexType::Debug(r, g, b) => [r, g, b],
+                    _ => {
+                        let color = colors.get_color_u8(&hex_type);
+                        [color.0, color.1, color.2]
+                    }
+                };
+                rectangles.push((rectangle(cursor, (swatch, swatch)), color));
+                let text_left = cursor.0 + swatch + self.text_size / 2.0;
+                texts.push(PlacedLabel{center: (text_left + text_width(&name, self.text_size) / 2.0, cursor.1 + swatch / 2.0), text: name, size: self.text_size});
+                cursor.0 += entry_width + self.text_size * 2.0;
+            }
+            cursor = (PADDING, cursor.1 + row_height);
+        }
+
+        if let Some(length) = self.scale_bar {
+            // segments alternate between text and background color, one segment per hex
+            let bar_height = self.text_size / 2.0;
+            let top = cursor.1 + (swatch - bar_height) / 2.0;
+            rectangles.push((rectangle((cursor.0 - 0.02, top - 0.02), (length as f32 + 0.04, bar_height + 0.04)), self.text_color));
+            for segment in (1..length).step_by(2) {
+                rectangles.push((rectangle((cursor.0 + segment as f32, top), (1.0, bar_height)), self.background));
+            }
+            let text = if length == 1 { String::from("1 hex") } else { format!("{} hexes", length) };
+            let text_left = cursor.0 + length as f32 + self.text_size / 2.0;
+            texts.push(PlacedLabel{center: (text_left + text_width(&text, self.text_size) / 2.0, cursor.1 + swatch / 2.0), text, size: self.text_size});
+            cursor.1 += row_height;
+        }
+
+        LegendLayout{height: cursor.1, rectangles, texts}
+    }
+}
+
+impl Default for Legend {
+    fn default() -> Legend {
+        Legend{
+            show_terrain: false,
+            scale_bar: None,
+            text_size: 0.3,
+            text_color: [20, 20, 20],
+            background: [240, 240, 240]
+        }
+    }
+}
+
+fn rectangle(position: (f32, f32), size: (f32, f32)) -> Polygon {
+    let (x, y) = position;
+    let mut points = vec![(x, y), (x + size.0, y), (x + size.0, y + size.1), (x, y + size.1)];
+    orient(&mut points);
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderers::{Basic, Renderer};
+
+    #[test]
+    fn lists_present_types() {
+        let mut map = HexMap::new(10, 10);
+        map.fill(HexType::Ocean);
+        map.field[5].terrain_type = HexType::Desert;
+        map.field[6].terrain_type = HexType::Field;
+        assert_eq!(vec![HexType::Field, HexType::Desert, HexType::Ocean], Legend::terrain_types(&map));
+
+        let legend = Legend{show_terrain: true, scale_bar: Some(3), ..Default::default()};
+        let layout = legend.layout(&map, &ColorMap::new(), 10.0);
+        assert_eq!(vec!["Field", "Desert", "Ocean", "3 hexes"], layout.texts.iter().map(|text| text.text.as_str()).collect::<Vec<_>>());
+        // swatches, scale bar and its middle segment
+        assert_eq!(3 + 2, layout.rectangles.len());
+
+        // narrow panel puts every entry on separate row
+        let narrow = legend.layout(&map, &ColorMap::new(), 1.0);
+        assert!(narrow.height > layout.height);
+
+        let mut renderer = Basic::default();
+        renderer.set_scale(20.0);
+        let height = renderer.render(&map).height();
+        renderer.legend = legend;
+        let image = renderer.render(&map);
+        assert_eq!(height + (layout.height * 20.0).ceil() as u32, image.height());
+        assert_eq!(&legend.background, image.get_pixel(image.width() - 1, image.height() - 1));
+    }
+}
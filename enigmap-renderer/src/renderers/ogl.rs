@@ -4,12 +4,22 @@ use rand::prelude::*;
 
 use enigmap::{HexMap, Hex, HexType, RATIO};
 
-use crate::renderers::{Image, Renderer, get_hex_vertex, ColorMode};
+use crate::renderers::{Image, Renderer, RenderError, get_hex_vertex, ColorMode};
 use crate::renderers::colors::ColorMap;
 
 /// Basic hardware renderer
 /// 
 /// Supports multisampling
+/// ## Usage
+/// Use `OGL::try_new` on machines, which may not have GPU, `OGL::default` panics without OpenGL context
+/// ```no_run
+/// use enigmap_renderer::renderers::OGL;
+///
+/// match OGL::try_new() {
+///     Ok(renderer) => {},
+///     Err(error) => eprintln!("{}", error)
+/// }
+/// ```
 pub struct OGL {
     /// Size of `Hex` on X axis in pixels
     multiplier: f32,
@@ -31,6 +41,33 @@ pub struct OGL {
 }
 
 impl OGL {
+    /// Creates renderer, returns error when OpenGL context or shaders can't be created
+    pub fn try_new() -> Result<OGL, RenderError> {
+        let tile_size = 1024;
+        let event_loop = create_event_loop()?;
+        let size = glutin::dpi::PhysicalSize::new(tile_size, tile_size);
+        let context = glutin::ContextBuilder::new().with_multisampling(8).build_headless(&event_loop, size)
+            .map_err(|error| RenderError::Context(error.to_string()))?;
+        let headless = HeadlessRenderer::new(context).map_err(|error| RenderError::Context(error.to_string()))?;
+
+        // keep shaders in different files and include them on compile
+        let vertex_shader_src = include_str!("vert.glsl");
+        let fragment_shader_src = include_str!("frag.glsl");
+
+        let program = Program::from_source(&headless, vertex_shader_src, fragment_shader_src, None).map_err(RenderError::open_gl)?;
+
+        Ok(OGL{
+            multiplier: 50.0,
+            wrap_map: true,
+            randomize_colors: true,
+            tile_size,
+            colors: ColorMap::new(),
+            headless,
+            _event_loop: event_loop,
+            program
+        })
+    }
+
     /// Returns `Vec` of arranged `Hex` vertices
     fn get_hex_points(&self, hex: &Hex) -> Vec<Vertex> {
         let mut verts: Vec<Vertex> = Vec::new();
@@ -51,6 +88,11 @@ impl Renderer for OGL {
     type Output = Image;
 
     fn render(&self, map: &HexMap) -> Image {
+        self.try_render(map).unwrap()
+    }
+
+    fn try_render(&self, map: &HexMap) -> Result<Image, RenderError> {
+        self.colors.validate(map)?;
         let tiles_x = ((map.absolute_size_x * self.multiplier) / self.tile_size as f32).ceil() as u32;
         let tiles_y = ((map.absolute_size_y * self.multiplier) / self.tile_size as f32).ceil() as u32;
 
@@ -58,7 +100,7 @@ impl Renderer for OGL {
 
         let shape: Vec<Vertex> = self.get_hex_points(&map.field[0]);
         
-        let vertex_buffer = VertexBuffer::new(&self.headless, &shape).unwrap();
+        let vertex_buffer = VertexBuffer::new(&self.headless, &shape).map_err(RenderError::open_gl)?;
 
         let indices = index::NoIndices(index::PrimitiveType::TriangleStrip);
 
@@ -73,12 +115,12 @@ impl Renderer for OGL {
 
             implement_vertex!(Attr, world_position, color);
 
-            let data = map.field.iter().map(|hex| {
+            let data = map.field.iter().map(|hex| -> Result<Vec<Attr>, RenderError> {
                 let color_diff = rng.gen_range(0.98, 1.02);
                 let mut color = match hex.terrain_type {
                     HexType::Debug(val_x , val_y, val_z) => (val_x as f32 * 256.0, val_y as f32 * 256.0, val_z as f32 * 256.0),
                     _ => {
-                        let color = self.colors.get_color_f32(&hex.terrain_type);
+                        let color = self.colors.try_get_color_f32(&hex.terrain_type)?;
                         (color.r, color.g, color.b)
                     }
                 };
@@ -102,13 +144,13 @@ impl Renderer for OGL {
                     vec.push(Attr{world_position: (vec[0].world_position.0 - map.size_x as f32, vec[0].world_position.1), ..vec[0]});
                     vec.push(Attr{world_position: (vec[0].world_position.0 + map.size_x as f32, vec[0].world_position.1), ..vec[0]});
                 }
-                vec
-            }).flatten().collect::<Vec<_>>();
+                Ok(vec)
+            }).collect::<Result<Vec<_>, _>>()?.concat();
 
-            vertex::VertexBuffer::new(&self.headless, &data).unwrap()
+            vertex::VertexBuffer::new(&self.headless, &data).map_err(RenderError::open_gl)?
         };
 
-        let texture = Texture2d::empty(&self.headless, 1024, 1024).unwrap();
+        let texture = Texture2d::empty(&self.headless, 1024, 1024).map_err(RenderError::open_gl)?;
         let mut target = texture.as_surface();
 
         // rendering
@@ -130,16 +172,16 @@ impl Renderer for OGL {
                 ];
 
                 let uniforms = uniform!{transform: transform};
-                target.draw((&vertex_buffer, per_instance.per_instance().unwrap()),
-                    &indices, &self.program, &uniforms, &Default::default()).unwrap();
+                let instances = per_instance.per_instance().map_err(|_| RenderError::instancing())?;
+                target.draw((&vertex_buffer, instances), indices, &self.program, &uniforms, &Default::default()).map_err(RenderError::open_gl)?;
 
                 // reading the front buffer into an image
                 let image: texture::RawImage2d<u8> = texture.read();
-                Self::add_tile_to_image(&image.data, &mut final_image_buffer, map, self.multiplier, 1024, x as usize, y as usize);
+                Self::try_add_tile_to_image(&image.data, &mut final_image_buffer, map, self.multiplier, 1024, x as usize, y as usize)?;
             }
         }
 
-        Image::from_buffer(target_size_x as u32, target_size_y as u32, final_image_buffer, ColorMode::Rgba)
+        Ok(Image::from_buffer(target_size_x as u32, target_size_y as u32, final_image_buffer, ColorMode::Rgba))
     }
 
     fn set_scale(&mut self, scale: f32) {
@@ -158,38 +200,37 @@ impl Renderer for OGL {
 
 impl Default for OGL {
     fn default() -> OGL {
-        let tile_size = 1024;
-        let event_loop = EventLoop::new();
-        let size = glutin::dpi::PhysicalSize::new(tile_size, tile_size);
-        let context = glutin::ContextBuilder::new().with_multisampling(8).build_headless(&event_loop, size).unwrap();
-        let headless = HeadlessRenderer::new(context).unwrap();
-
-        implement_vertex!(Vertex, position);
-
-        // keep shaders in different files and include them on compile
-        let vertex_shader_src = include_str!("vert.glsl");
-        let fragment_shader_src = include_str!("frag.glsl");
-
-        let program = Program::from_source(&headless, vertex_shader_src, fragment_shader_src, None).unwrap();
+        OGL::try_new().unwrap()
+    }
+}
 
-        OGL{
-            multiplier: 50.0,
-            wrap_map: true,
-            randomize_colors: true,
-            tile_size,
-            colors: ColorMap::new(),
-            headless,
-            _event_loop: event_loop,
-            program
-        }
+/// Creates event loop for headless OpenGL context, returns error when there is no usable display
+///
+/// X11 is tried first, Wayland is used only when `WAYLAND_DISPLAY` is set, because winit panics when it can't connect to it.
+#[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+pub(crate) fn create_event_loop() -> Result<EventLoop<()>, RenderError> {
+    use glium::glutin::platform::unix::EventLoopExtUnix;
+
+    match EventLoop::new_x11_any_thread() {
+        Ok(event_loop) => Ok(event_loop),
+        Err(_) if std::env::var_os("WAYLAND_DISPLAY").is_some() => Ok(EventLoop::new_wayland_any_thread()),
+        Err(error) => Err(RenderError::Context(error.to_string()))
     }
 }
 
+/// Creates event loop for headless OpenGL context
+#[cfg(not(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+pub(crate) fn create_event_loop() -> Result<EventLoop<()>, RenderError> {
+    Ok(EventLoop::new())
+}
+
 #[derive(Copy, Clone)]
 struct Vertex {
     position: [f32; 3],
 }
 
+implement_vertex!(Vertex, position);
+
 impl Vertex {
     pub fn from_tupple(coords: (f32, f32)) -> Vertex {
         Vertex{position: [coords.0, coords.1, 0.0]}
@@ -0,0 +1,127 @@
+use enigmap::{HexMap, HexType};
+
+use crate::renderers::{EDGE_DIRECTIONS, get_hex_index, get_hex_vertex};
+use crate::renderers::decor::{Polygon, orient};
+
+/// Which hex edges are stroked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutlineMode {
+    None,
+    /// Every hex edge
+    Grid,
+    /// Edges between different terrain, `Water` and `Ocean` are the same terrain
+    Borders,
+}
+
+/// Style of hex outlines
+///
+/// Width is relative to the width of `Hex`.
+/// ## Usage
+/// ```
+/// use enigmap_renderer::renderers::{Basic, Outline, OutlineMode};
+///
+/// let mut renderer = Basic::default();
+/// renderer.outline = Outline{mode: OutlineMode::Grid, width: 0.02, color: [0, 0, 0]};
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Outline {
+    pub mode: OutlineMode,
+    pub width: f32,
+    pub color: [u8; 3],
+}
+
+impl Outline {
+    /// Returns strokes of outlined edges as polygons with their color
+    ///
+    /// Every edge is returned once, polygons are in counter clockwise order
+    pub(crate) fn polygons(&self, map: &HexMap, wrap_map: bool) -> Vec<(Polygon, [u8; 3])> {
+        let mut polygons = Vec::new();
+        if self.mode == OutlineMode::None {
+            return polygons;
+        }
+
+        for hex in &map.field {
+            for (edge, &(dx, dy)) in EDGE_DIRECTIONS.iter().enumerate() {
+                let other = get_hex_index(map, hex.x + dx, hex.y + dy, wrap_map).map(|index| &map.field[index]);
+                // edges between two hexes are owned by the hex on the left or above
+                let owned = edge < 3;
+                let stroke = match other {
+                    Some(other) if owned => self.mode == OutlineMode::Grid || terrain_class(hex.terrain_type) != terrain_class(other.terrain_type),
+                    Some(_) => false,
+                    None => self.mode == OutlineMode::Grid
+                };
+                if stroke {
+                    let from = get_hex_vertex(hex, edge);
+                    let to = get_hex_vertex(hex, (edge + 1) % 6);
+                    polygons.push((self.stroke(from, to), self.color));
+                }
+            }
+        }
+        polygons
+    }
+
+    /// Returns rectangle around edge, extended by half of the width to join neighbouring strokes
+    fn stroke(&self, from: (f32, f32), to: (f32, f32)) -> Polygon {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        let (dx, dy) = (dx / length * self.width / 2.0, dy / length * self.width / 2.0);
+        let mut points = vec![
+            (from.0 - dx + dy, from.1 - dy - dx),
+            (from.0 - dx - dy, from.1 - dy + dx),
+            (to.0 + dx - dy, to.1 + dy + dx),
+            (to.0 + dx + dy, to.1 + dy - dx)
+        ];
+        orient(&mut points);
+        points
+    }
+}
+
+impl Default for Outline {
+    fn default() -> Outline {
+        Outline{mode: OutlineMode::None, width: 0.04, color: [30, 30, 30]}
+    }
+}
+
+/// Returns terrain with merged water types
+fn terrain_class(hex_type: HexType) -> HexType {
+    match hex_type {
+        HexType::Ocean => HexType::Water,
+        other => other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderers::{Basic, Renderer};
+
+    #[test]
+    fn strokes_edges() {
+        let mut map = HexMap::new(10, 10);
+        map.fill(HexType::Field);
+        let water = map.coords_to_index(2, 4).unwrap();
+        map.field[water].terrain_type = HexType::Ocean;
+        map.field[water + 1].terrain_type = HexType::Water;
+
+        let grid = Outline{mode: OutlineMode::Grid, ..Default::default()};
+        // three edges per hex and top of the map
+        assert_eq!(3 * 100 + 2 * 10, grid.polygons(&map, true).len());
+        assert!(grid.polygons(&map, false).len() > 3 * 100 + 2 * 10);
+        let borders = Outline{mode: OutlineMode::Borders, width: 0.2, ..Default::default()};
+        assert_eq!(10, borders.polygons(&map, true).len());
+
+        let mut renderer = Basic::default();
+        renderer.set_scale(20.0);
+        renderer.set_random_colors(false);
+        renderer.outline = borders;
+        let image = renderer.render(&map);
+        let pixel = |point: (f32, f32)| image.get_pixel((point.0 * 20.0) as u32, (point.1 * 20.0) as u32).to_vec();
+
+        // between water hexes and between fields
+        let (x, y) = map.field[water].center();
+        assert_ne!(borders.color.to_vec(), pixel((x + 0.5, y)));
+        assert_ne!(borders.color.to_vec(), pixel((x + 2.5, y)));
+        // coast
+        assert_eq!(borders.color.to_vec(), pixel((x - 0.5, y)));
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use enigmap::HexMap;
+
+use crate::renderers::{EDGE_DIRECTIONS, QUARTER_RATIO, get_hex_index, get_hex_vertex};
+use crate::renderers::decor::Polygon;
+
+/// Contiguous area of hexes with the same `HexType`
+pub(crate) struct Region {
+    /// Indices of hexes in `HexMap::field`
+    pub hexes: Vec<usize>,
+    /// Closed boundaries in clockwise order, holes are counter clockwise
+    pub boundaries: Vec<Polygon>,
+}
+
+/// Splits map into regions of the same `HexType`
+///
+/// With `wrap_map` regions continue over left and right map edge, their boundaries are split by the edge.
+pub(crate) fn find_regions(map: &HexMap, wrap_map: bool) -> Vec<Region> {
+    let mut region_ids = vec![usize::MAX; map.field.len()];
+    let mut regions = Vec::new();
+
+    for start in 0..map.field.len() {
+        if region_ids[start] != usize::MAX {
+            continue;
+        }
+        let id = regions.len();
+        let terrain = map.field[start].terrain_type;
+        let mut hexes = vec![start];
+        region_ids[start] = id;
+        let mut next = 0;
+        while next < hexes.len() {
+            let hex = &map.field[hexes[next]];
+            for &(dx, dy) in &EDGE_DIRECTIONS {
+                if let Some(other) = get_hex_index(map, hex.x + dx, hex.y + dy, wrap_map) {
+                    if region_ids[other] == usize::MAX && map.field[other].terrain_type == terrain {
+                        region_ids[other] = id;
+                        hexes.push(other);
+                    }
+                }
+            }
+            next += 1;
+        }
+        regions.push(hexes);
+    }
+
+    regions.into_iter().enumerate().map(|(id, hexes)| {
+        let boundaries = trace_boundaries(map, &hexes, &region_ids, id);
+        Region{hexes, boundaries}
+    }).collect()
+}
+
+/// Joins hex edges on the region border into closed loops
+fn trace_boundaries(map: &HexMap, hexes: &[usize], region_ids: &[usize], id: usize) -> Vec<Polygon> {
+    // hex edges go clockwise, so every boundary vertex has exactly one outgoing edge
+    let mut edges = BTreeMap::new();
+    for &index in hexes {
+        let hex = &map.field[index];
+        for (edge, &(dx, dy)) in EDGE_DIRECTIONS.iter().enumerate() {
+            // neighbours over the wrapped edge are not adjacent in the image
+            let inside = get_hex_index(map, hex.x + dx, hex.y + dy, false).map(|other| region_ids[other]) == Some(id);
+            if !inside {
+                let from = get_hex_vertex(hex, edge);
+                let to = get_hex_vertex(hex, (edge + 1) % 6);
+                edges.insert(vertex_key(from), (from, vertex_key(to)));
+            }
+        }
+    }
+
+    let mut boundaries = Vec::new();
+    while let Some(&start) = edges.keys().next() {
+        let mut boundary = Vec::new();
+        let mut current = start;
+        while let Some((point, next)) = edges.remove(&current) {
+            boundary.push(point);
+            current = next;
+        }
+        boundaries.push(boundary);
+    }
+    boundaries
+}
+
+/// Returns vertex position as integers, so shared vertices of neighbouring hexes are equal
+fn vertex_key(point: (f32, f32)) -> (i32, i32) {
+    ((point.0 * 2.0).round() as i32, (point.1 / QUARTER_RATIO).round() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enigmap::HexType;
+
+    #[test]
+    fn regions_with_holes() {
+        let mut map = HexMap::new(10, 10);
+        map.fill(HexType::Field);
+        let lake = map.coords_to_index(3, 4).unwrap();
+        map.field[lake].terrain_type = HexType::Water;
+        // region over wrapped edge
+        for &row in &[0, 10] {
+            map.field[row].terrain_type = HexType::Ice;
+            map.field[row + 9].terrain_type = HexType::Ice;
+        }
+
+        let regions = find_regions(&map, true);
+        assert_eq!(3, regions.len());
+        let field = regions.iter().find(|region| map.field[region.hexes[0]].terrain_type == HexType::Field).unwrap();
+        assert_eq!(100 - 1 - 4, field.hexes.len());
+        // outer boundary and lake
+        assert_eq!(2, field.boundaries.len());
+        assert!(field.boundaries.iter().any(|boundary| boundary.len() == 6));
+
+        let ice = regions.iter().find(|region| map.field[region.hexes[0]].terrain_type == HexType::Ice).unwrap();
+        assert_eq!(4, ice.hexes.len());
+        assert_eq!(2, ice.boundaries.len());
+
+        assert_eq!(4, find_regions(&map, false).len());
+    }
+}
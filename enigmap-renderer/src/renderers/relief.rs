@@ -0,0 +1,136 @@
+use enigmap::{HexMap, Hex};
+
+use crate::renderers::{RenderError, EDGE_DIRECTIONS, get_hex_index};
+
+/// Hillshading of hexes from their elevation
+///
+/// Every hex is lit by its slope towards the light, hexes without elevation are not shaded.
+/// ## Usage
+/// ```
+/// use enigmap::HexMap;
+/// use enigmap_renderer::renderers::{Basic, Relief};
+///
+/// let map = HexMap::new(20, 10);
+/// let elevation = map.field.iter().map(|hex| hex.x as f32 * 0.1).collect();
+///
+/// let mut renderer = Basic::default();
+/// // light from the north-west
+/// renderer.relief = Relief{azimuth: 315.0, ..Relief::with_elevation(elevation)};
+/// ```
+#[derive(Debug, Clone)]
+pub struct Relief {
+    /// Elevation of every hex in `HexMap::field`, in units of hex width
+    pub elevation: Option<Vec<f32>>,
+    /// Direction of light in degrees, clockwise from the north
+    pub azimuth: f32,
+    /// Angle of light above horizon in degrees
+    pub altitude: f32,
+    /// Multiplier of elevation when computing slopes
+    pub exaggeration: f32,
+    /// How much does shading change colors, in range `<0; 1>`
+    pub strength: f32,
+    /// How much are higher hexes lighter and lower hexes darker
+    pub tint: f32,
+}
+
+impl Relief {
+    /// Creates relief with default light from given elevation
+    pub fn with_elevation(elevation: Vec<f32>) -> Relief {
+        Relief{elevation: Some(elevation), ..Default::default()}
+    }
+
+    /// Checks that elevation has the same length as `HexMap::field`
+    pub fn validate(&self, map: &HexMap) -> Result<(), RenderError> {
+        match &self.elevation {
+            Some(elevation) if elevation.len() != map.field.len() => Err(RenderError::Elevation{size: elevation.len(), expected: map.field.len()}),
+            _ => Ok(())
+        }
+    }
+
+    /// Returns color multiplier of every hex in `HexMap::field`, `None` without elevation
+    /// # Panics
+    /// when elevation has different length than `HexMap::field`
+    pub fn shades(&self, map: &HexMap, wrap_map: bool) -> Option<Vec<f32>> {
+        if let Err(error) = self.validate(map) {
+            panic!("{}", error);
+        }
+        let elevation = self.elevation.as_ref()?;
+
+        let (azimuth, altitude) = (self.azimuth.to_radians(), self.altitude.to_radians());
+        // y axis points down
+        let light = (azimuth.sin() * altitude.cos(), -azimuth.cos() * altitude.cos(), altitude.sin());
+
+        let min = elevation.iter().cloned().fold(f32::MAX, f32::min);
+        let max = elevation.iter().cloned().fold(f32::MIN, f32::max);
+        let range = (max - min).max(f32::EPSILON);
+
+        let shades = map.field.iter().zip(elevation).map(|(hex, &height)| {
+            let (gradient_x, gradient_y) = self.gradient(map, hex, height, elevation, wrap_map);
+            let normal = (-gradient_x, -gradient_y, 1.0);
+            let length = (normal.0 * normal.0 + normal.1 * normal.1 + normal.2 * normal.2).sqrt();
+            let lit = ((normal.0 * light.0 + normal.1 * light.1 + normal.2 * light.2) / length).max(0.0);
+
+            // flat hexes keep their color
+            let shade = 1.0 + self.strength * (lit / light.2.max(f32::EPSILON) - 1.0);
+            let tint = 1.0 + self.tint * ((height - min) / range - 0.5);
+            shade * tint
+        }).collect();
+        Some(shades)
+    }
+
+    /// Least squares slope from elevation of neighbours
+    fn gradient(&self, map: &HexMap, hex: &Hex, height: f32, elevation: &[f32], wrap_map: bool) -> (f32, f32) {
+        let center = hex.center();
+        let mut sums = (0.0, 0.0, 0.0, 0.0);
+        for &(dx, dy) in &EDGE_DIRECTIONS {
+            let index = match get_hex_index(map, hex.x + dx, hex.y + dy, wrap_map) {
+                Some(index) => index,
+                None => continue
+            };
+            let (x, y) = Hex::from_coords(hex.x + dx, hex.y + dy).center();
+            let (offset_x, offset_y) = (x - center.0, y - center.1);
+            let difference = (elevation[index] - height) * self.exaggeration;
+            sums.0 += difference * offset_x;
+            sums.1 += offset_x * offset_x;
+            sums.2 += difference * offset_y;
+            sums.3 += offset_y * offset_y;
+        }
+        let divide = |value: f32, by: f32| if by > 0.0 { value / by } else { 0.0 };
+        (divide(sums.0, sums.1), divide(sums.2, sums.3))
+    }
+}
+
+impl Default for Relief {
+    fn default() -> Relief {
+        Relief{
+            elevation: None,
+            azimuth: 315.0,
+            altitude: 45.0,
+            exaggeration: 1.0,
+            strength: 0.6,
+            tint: 0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slopes_facing_light_are_lighter() {
+        let map = HexMap::new(20, 10);
+        assert!(Relief::default().shades(&map, false).is_none());
+
+        // rising to the east
+        let elevation = map.field.iter().map(|hex| hex.center().0 * 0.5).collect::<Vec<_>>();
+        let index = map.coords_to_index(5, 4).unwrap();
+        let from_west = Relief{azimuth: 270.0, ..Relief::with_elevation(elevation.clone())};
+        let from_east = Relief{azimuth: 90.0, ..Relief::with_elevation(elevation.clone())};
+        let from_north = Relief{azimuth: 0.0, ..Relief::with_elevation(elevation)};
+
+        assert!(from_west.shades(&map, false).unwrap()[index] > 1.0);
+        assert!(from_east.shades(&map, false).unwrap()[index] < 1.0);
+        assert!((from_north.shades(&map, false).unwrap()[index] - 1.0).abs() < 0.3);
+    }
+}
@@ -1,20 +1,16 @@
 use glium::*;
-use glium::backend::glutin::DisplayCreationError;
 use glium::glutin::event_loop::EventLoop;
 
 use rand::prelude::*;
-use png::Decoder;
-use toml::Value;
 
-use std::path::Path;
 use std::f32;
-use std::fs::File;
-use std::io::{ErrorKind, Read};
 use std::collections::HashMap;
 
 use enigmap::{HexMap, Hex, HexType, RATIO, HEX_TYPE_STRINGS};
 
-use crate::renderers::{Image, Renderer, ColorMode, get_hex_vertex};
+use crate::renderers::{Image, Renderer, RenderError, ColorMode, get_hex_vertex};
+use crate::renderers::ogl::create_event_loop;
+use crate::renderers::textures::{TextureSet, Setting};
 
 /// Textured hardware renderer
 /// 
@@ -30,18 +26,11 @@ pub struct Sprite {
     multiplier: f32,
     /// Should the map repeat on the X axis
     wrap_map: bool,
-    /// Path to folder with textures
-    texture_folder: Option<String>,
 
     tile_size: u32,
 
-    random_rotation: Setting,
-    random_color: Setting,
-    render_in_25d: Setting,
-    variations: HashMap<String, u32>,
-    variations_cover: HashMap<String, u32>,
-    textures: HashMap<String, Vec<Image>>,
-    textures_cover: HashMap<String, Vec<Image>>,
+    /// Textures and their settings
+    textures: TextureSet,
     /// Rendering target
     headless: HeadlessRenderer,
     /// Event loop for the window
@@ -77,161 +66,61 @@ impl Sprite {
     ///     let renderer = Sprite::from_folder("./examples/textures");
     ///     // now all the textures and settings are loaded
     /// ```
+    /// # Panics
+    /// when OpenGL context can't be created or texture can't be decoded
     pub fn from_folder(folder: &str) -> Sprite {
+        Self::try_from_folder(folder).unwrap()
+    }
+
+    /// Creates new instance of Sprite using specified folder as a source of textures
+    ///
+    /// Returns error when OpenGL context can't be created or texture can't be decoded
+    pub fn try_from_folder(folder: &str) -> Result<Sprite, RenderError> {
+        Self::with_textures(TextureSet::from_folder(folder)?)
+    }
+
+    /// Creates new instance of Sprite with error textures, returns error when OpenGL context can't be created
+    pub fn try_new() -> Result<Sprite, RenderError> {
+        Self::with_textures(TextureSet::default())
+    }
+
+    fn with_textures(textures: TextureSet) -> Result<Sprite, RenderError> {
         // try to construct window
         let tile_size = 1024;
 
-        let (headless, event_loop) = Self::create_display(tile_size).unwrap();
-        let (program, program_cover, program_debug) = Self::create_programs(&headless);
+        let (headless, event_loop) = Self::create_display(tile_size)?;
+        let (program, program_cover, program_debug) = Self::create_programs(&headless)?;
 
-        let mut empty_variations = HashMap::new();
-        let mut empty_variations_cover = HashMap::new();
-        let textures = HashMap::new();
-        let textures_cover = HashMap::new();
-        for hextype in HEX_TYPE_STRINGS.keys() {
-            empty_variations.insert((**hextype).to_owned(), 1);
-            empty_variations_cover.insert((**hextype).to_owned(), 1);
-        }
-        let mut renderer = Sprite{
+        Ok(Sprite{
             multiplier: 50.0,
             wrap_map: true,
-            texture_folder: Some(folder.to_string()),
-            random_color: Setting::None,
-            random_rotation: Setting::All,
-            render_in_25d: Setting::None,
-            variations: empty_variations,
-            variations_cover: empty_variations_cover,
-            tile_size: 1024,
+            tile_size,
             textures,
-            textures_cover,
             _event_loop: event_loop,
             headless,
             program,
             program_cover,
             program_debug
-        };
-        // check for path
-        if !Path::new(folder).exists() {
-            println!("WARNING! Path does not exist, renderer will use blank textures.");
-            renderer.texture_folder = None;
-            return renderer;
-        }
-        // check for settings file
-        let mut file = match File::open(folder.to_owned() + "/settings.toml") {
-            Ok(file) => file,
-            Err(error) => {
-                // don't write warning when file is non-existent
-                if let ErrorKind::NotFound = error.kind() {
-                    println!("dafuq");
-                    return renderer;
-                }
-                println!("WARNING! Error when opening settings file. Renderer will use default settings.");
-                return renderer;
-            }
-        };
-        let mut settings = String::new();
-        // load contents and handle errors
-        if file.read_to_string(&mut settings).is_err() {
-            println!("WARNING! Error when reading settings file. Renderer will use default settings.");
-            return renderer;
-        }
-        let settings = settings.parse::<Value>().unwrap();
-        
-        // parse results
-        if let Some(val) = Sprite::parse_setting("random_rotation", &settings) {
-            renderer.random_rotation = val;
-        }
-        if let Some(val) = Sprite::parse_setting("random_color", &settings) {
-            renderer.random_color = val;
-        }
-        if let Some(val) = Sprite::parse_setting("render_in_25d", &settings) {
-            renderer.render_in_25d = val;
-        }
-        // check for variations table
-        if let Some(val) = settings.get("variations") {
-            // check if it is table
-            if let Value::Table(table) = val {
-                // iterate over all hextpyes
-                for (hextype, variations) in &mut renderer.variations {
-                    // check if valie is in table
-                    if let Some(number) = table.get(hextype) {
-                        // check if value in table as a number
-                        if let Value::Integer(int) = number {
-                            *variations = *int as u32;
-                        }
-                    }
-                }
-            }
-        }
-
-        if let Some(val) = settings.get("variations_cover") {
-            // check if it is table
-            if let Value::Table(table) = val {
-                // iterate over all hextpyes
-                for (hextype, variations) in &mut renderer.variations_cover {
-                    // check if valie is in table
-                    if let Some(number) = table.get(hextype) {
-                        // check if value in table as a number
-                        if let Value::Integer(int) = number {
-                            *variations = *int as u32;
-                        }
-                    }
-                }
-            }
-        }
-        renderer.load_texture_data();
-
-        renderer
+        })
     }
 
     /// Sets texture to a specified `HexType`
     /// Expects RGBA image data
     /// Won't overwrite variations
     pub fn set_texture(&mut self, image_data: &[u8], width: u32, height: u32, tile: HexType, is_cover: bool) {
-        if image_data.len() != (width * height * 4) as usize {
-            eprintln!("Warning, image buffer length different than expected");
-        }
-        let key = String::from(tile);
-        if is_cover {
-            self.textures_cover.get_mut(&key).unwrap()[0] = Image::from_buffer(width, height, image_data.to_owned(), ColorMode::Rgba);
-        } else {
-            self.textures.get_mut(&key).unwrap()[0] = Image::from_buffer(width, height, image_data.to_owned(), ColorMode::Rgba);
-        }
+        self.textures.set_texture(image_data, width, height, tile, is_cover);
     }
 
     /// Adds texture variation to a specified `HexType`
     /// Expects RGBA image data
     pub fn add_texture_variation(&mut self, image_data: &[u8], width: u32, height: u32, tile: HexType, is_cover: bool) {
-        if image_data.len() != (width * height * 4) as usize {
-            eprintln!("Warning, image buffer length different than expected");
-        }
-        let key = String::from(tile);
-        if is_cover {
-            self.textures_cover.get_mut(&key).unwrap().push(Image::from_buffer(width, height, image_data.to_owned(), ColorMode::Rgba));
-            *self.variations_cover.get_mut(&key).unwrap()+=1;
-        } else {
-            self.textures.get_mut(&key).unwrap().push(Image::from_buffer(width, height, image_data.to_owned(), ColorMode::Rgba));
-            *self.variations.get_mut(&key).unwrap()+=1;
-        }
+        self.textures.add_texture_variation(image_data, width, height, tile, is_cover);
     }
 
     /// Removes specific variation
     /// Won't remove anything if index is out of range or there is only one texture present
     pub fn remove_variation(&mut self, index: usize, tile: HexType, is_cover: bool) -> Option<usize> {
-        let key = String::from(tile);
-        if is_cover {
-            if self.variations_cover[&key] == 1 || index as u32 > self.variations_cover[&key] {
-                return None;
-            }
-            self.textures_cover.get_mut(&key).unwrap().remove(index);
-            Some(index)
-        } else {
-            if self.variations[&key] == 1 || index as u32 > self.variations[&key] {
-                return None;
-            }
-            self.textures.get_mut(&key).unwrap().remove(index);
-            Some(index)
-        }
+        self.textures.remove_variation(index, tile, is_cover)
     }
 
     /// Enable or disable random tint on specific hexes
@@ -244,7 +133,7 @@ impl Sprite {
     /// renderer.set_random_color(setting);
     /// ```
     pub fn set_random_color(&mut self, setting: Setting) {
-        self.random_color = setting;
+        self.textures.random_color = setting;
     }
 
     /// Enable or disable random rotation on specific hexes
@@ -257,7 +146,7 @@ impl Sprite {
     /// renderer.set_random_rotation(setting);
     /// ```
     pub fn set_random_rotation(&mut self, setting: Setting) {
-        self.random_rotation = setting;
+        self.textures.random_rotation = setting;
     }
 
     /// Enable or disable 2.5D rendering on specific hexes
@@ -270,136 +159,35 @@ impl Sprite {
     /// renderer.set_cover(setting);
     /// ```
     pub fn set_cover(&mut self, setting: Setting) {
-        self.render_in_25d = setting;
+        self.textures.render_in_25d = setting;
     }
 
-    fn parse_setting(setting: &str, settings: &Value) -> Option<Setting> {
-        if let Some(val) = settings.get(setting) {
-            match val {
-                Value::Array(arr) => Some(Setting::parse_array(arr)),
-                Value::Boolean(boo) => {
-                    if *boo {
-                        Some(Setting::All)
-                    } else {
-                        Some(Setting::None)
-                    }
-                }
-                _ => None
-            }
-        } else {
-            None
-        }
-    }
-
-    fn load_textures(&self) -> HashMap<String, Vec<glium::texture::texture2d::Texture2d>> {
+    fn load_textures(&self) -> Result<HashMap<String, Vec<glium::texture::texture2d::Texture2d>>, RenderError> {
         let mut textures: HashMap<String, Vec<glium::texture::texture2d::Texture2d>> = HashMap::new();
-        for key in self.textures.keys() {
+        for key in self.textures.textures.keys() {
             textures.insert(key.to_owned(), Vec::new());
-            for texture in self.textures.get(key).unwrap() {
+            for texture in self.textures.textures.get(key).unwrap() {
                 let image = glium::texture::RawImage2d::from_raw_rgba_reversed(texture.buffer(), (texture.width(), texture.height()));
-                let texture = glium::texture::Texture2d::new(&self.headless, image).unwrap();
+                let texture = glium::texture::Texture2d::new(&self.headless, image).map_err(RenderError::open_gl)?;
                 textures.get_mut(key).unwrap().push(texture);
             }
         }
 
-        textures
+        Ok(textures)
     }
 
-    fn load_cover_textures(&self) -> HashMap<String, Vec<glium::texture::texture2d::Texture2d>> {
+    fn load_cover_textures(&self) -> Result<HashMap<String, Vec<glium::texture::texture2d::Texture2d>>, RenderError> {
         let mut textures: HashMap<String, Vec<glium::texture::texture2d::Texture2d>> = HashMap::new();
-        for key in self.textures_cover.keys() {
+        for key in self.textures.textures_cover.keys() {
             textures.insert(key.to_owned(), Vec::new());
-            for texture in self.textures_cover.get(key).unwrap() {
+            for texture in self.textures.textures_cover.get(key).unwrap() {
                 let image = glium::texture::RawImage2d::from_raw_rgba_reversed(texture.buffer(), (texture.width(), texture.height()));
-                let texture = glium::texture::Texture2d::new(&self.headless, image).unwrap();
+                let texture = glium::texture::Texture2d::new(&self.headless, image).map_err(RenderError::open_gl)?;
                 textures.get_mut(key).unwrap().push(texture);
             }
         }
 
-        textures
-    }
-
-    fn load_texture_data(&mut self) {
-        for key in HEX_TYPE_STRINGS.keys() {
-            if !self.textures.contains_key(*key) {
-                self.textures.insert((**key).to_owned(), Vec::new());
-            }
-            // if texture folder is not specified, use error texture instead
-            let texture = match &self.texture_folder {
-                Some(_) => self.texture_from_path(&format!("/{}.png", key)),
-                None => Self::generate_error_texture()
-            };
-            self.textures.get_mut(*key).unwrap().push(texture);
-            // check for alternative textures
-            let max_textures = self.variations[*key];
-            for i in 1..max_textures {
-                let texture = match &self.texture_folder {
-                    Some(_) => self.texture_from_path(&format!("/{}_{}.png", key, i)),
-                    None => Self::generate_error_texture()
-                };
-                self.textures.get_mut(*key).unwrap().push(texture);
-            }
-        }
-        // load cover textures
-        for key in HEX_TYPE_STRINGS.keys() {
-            match &self.render_in_25d {
-                Setting::None => break,
-                Setting::All => {},
-                Setting::Some(val) => {
-                    if !val.contains(&HEX_TYPE_STRINGS[*key]) {
-                        continue;
-                    }
-                }
-            };
-            if !self.textures_cover.contains_key(*key) {
-                self.textures_cover.insert((**key).to_owned(), Vec::new());
-            }
-            let texture = match &self.texture_folder {
-                Some(_) => self.texture_from_path(&format!("/{}_cover.png", key)),
-                None => Self::generate_error_texture()
-            };
-            self.textures_cover.get_mut(*key).unwrap().push(texture);
-            // check for alternative textures
-            let max_textures = self.variations_cover[*key];
-            for i in 1..max_textures {
-                let texture = match &self.texture_folder {
-                    Some(_) => self.texture_from_path(&format!("/{}_cover_{}.png", key, i)),
-                    None => Self::generate_error_texture()
-                };
-                self.textures_cover.get_mut(*key).unwrap().push(texture);
-            }
-        }
-    }
-
-    fn generate_error_texture() -> Image {
-        Image::from_fn_rgba(32, 32, |x, y| {
-            // create checkerboard
-            let odd_x = (x/4) % 2 == 1;
-            let odd_y = (y/4) % 2 == 1;
-            if (odd_x && !odd_y) || (!odd_x && odd_y) {
-                [0, 0, 0, 255]
-            } else {
-                [255, 0, 255, 255]
-            }
-        })
-    }
-
-    fn texture_from_path(&self, path: &str) -> Image {
-        let file = File::open(self.texture_folder.to_owned().unwrap() + path);
-        match file {
-            Ok(image) => {
-                let decoder = Decoder::new(image);
-                let (info, mut reader) = decoder.read_info().unwrap();
-                let mut buf = vec![0; info.buffer_size()];
-                // Read the next frame. Currently this function should only called once.
-                reader.next_frame(&mut buf).unwrap();
-                Image::from_buffer(info.width, info.height, buf, ColorMode::Rgba)
-            },
-            Err(_err) => {
-                eprintln!("texture {} not found", path);
-                Self::generate_error_texture()
-            }
-        }
+        Ok(textures)
     }
 
     fn get_rotation(index: u32) -> [[f32; 2];2] {
@@ -453,7 +241,7 @@ impl Sprite {
         (coords.0, coords.1, height)
     }
 
-    fn create_programs(headless: &HeadlessRenderer) -> (Program, Program, Program) {
+    fn create_programs(headless: &HeadlessRenderer) -> Result<(Program, Program, Program), RenderError> {
         // keep shaders in different files and include them on compile
         let vertex_shader_src = include_str!("vert_sprite.glsl");
         let vertex_shader_debug_src = include_str!("vert.glsl");
@@ -461,18 +249,19 @@ impl Sprite {
         let fragment_shader_cover_src = include_str!("frag_sprite_cover.glsl");
         let fragment_debug_src = include_str!("frag.glsl");
 
-        let program = Program::from_source(headless, vertex_shader_src, fragment_shader_src, None).unwrap();
-        let program_cover = Program::from_source(headless, vertex_shader_src, fragment_shader_cover_src, None).unwrap();
-        let program_debug = Program::from_source(headless, vertex_shader_debug_src, fragment_debug_src, None).unwrap();
+        let program = Program::from_source(headless, vertex_shader_src, fragment_shader_src, None).map_err(RenderError::open_gl)?;
+        let program_cover = Program::from_source(headless, vertex_shader_src, fragment_shader_cover_src, None).map_err(RenderError::open_gl)?;
+        let program_debug = Program::from_source(headless, vertex_shader_debug_src, fragment_debug_src, None).map_err(RenderError::open_gl)?;
 
-        (program, program_cover, program_debug)
+        Ok((program, program_cover, program_debug))
     }
 
-    fn create_display(tile_size: u32) -> Result<(HeadlessRenderer, EventLoop<()>), DisplayCreationError> {
-        let event_loop = EventLoop::new();
+    fn create_display(tile_size: u32) -> Result<(HeadlessRenderer, EventLoop<()>), RenderError> {
+        let event_loop = create_event_loop()?;
         let size = glutin::dpi::PhysicalSize::new(tile_size, tile_size);
-        let context = glutin::ContextBuilder::new().with_multisampling(8).with_depth_buffer(24).build_headless(&event_loop, size)?;
-        let display = HeadlessRenderer::new(context)?;
+        let context = glutin::ContextBuilder::new().with_multisampling(8).with_depth_buffer(24).build_headless(&event_loop, size)
+            .map_err(|error| RenderError::Context(error.to_string()))?;
+        let display = HeadlessRenderer::new(context).map_err(|error| RenderError::Context(error.to_string()))?;
 
         Ok((display, event_loop))
     }
@@ -482,19 +271,21 @@ impl Renderer for Sprite {
     type Output = Image;
 
     fn render(&self, map: &HexMap) -> Image {
+        self.try_render(map).unwrap()
+    }
+
+    fn try_render(&self, map: &HexMap) -> Result<Image, RenderError> {
         let tiles_x = ((map.absolute_size_x * self.multiplier) / self.tile_size as f32).ceil() as u32;
         let tiles_y = ((map.absolute_size_y * self.multiplier) / self.tile_size as f32).ceil() as u32;
 
-        implement_vertex!(Vertex, position, tex_coords);
-
-        let textures = self.load_textures();
-        let textures_cover = self.load_cover_textures();
+        let textures = self.load_textures()?;
+        let textures_cover = self.load_cover_textures()?;
 
         let shape: Vec<Vertex> = self.get_hex_points(&map.field[0]);
-        let vertex_buffer = VertexBuffer::new(&self.headless, &shape).unwrap();
+        let vertex_buffer = VertexBuffer::new(&self.headless, &shape).map_err(RenderError::open_gl)?;
 
         let shape_cover: Vec<Vertex> = Sprite::get_cover_shape();
-        let vertex_buffer_cover = VertexBuffer::new(&self.headless, &shape_cover).unwrap();
+        let vertex_buffer_cover = VertexBuffer::new(&self.headless, &shape_cover).map_err(RenderError::open_gl)?;
 
         let indices = index::NoIndices(index::PrimitiveType::TriangleStrip);
 
@@ -540,7 +331,7 @@ impl Renderer for Sprite {
                 Some(vec)
             }).flatten().collect::<Vec<_>>();
 
-            vertex::VertexBuffer::new(&self.headless, &data).unwrap()
+            vertex::VertexBuffer::new(&self.headless, &data).map_err(RenderError::open_gl)?
         };
 
         for key in HEX_TYPE_STRINGS.keys() {
@@ -548,9 +339,9 @@ impl Renderer for Sprite {
                 if hex.terrain_type != HEX_TYPE_STRINGS[*key] {
                     return None
                 }
-                Some(rng.gen_range::<u32, u32, u32>(1, self.variations[*key] + 1))
+                Some(rng.gen_range::<u32, u32, u32>(1, self.textures.variations[*key] + 1))
             }).collect::<Vec<u32>>();
-            for i in 1..=self.variations[*key] {
+            for i in 1..=self.textures.variations[*key] {
                 let mut colors_iter = colors.iter();
                 let data = map.field.iter().filter_map(|hex| {
                     let hex_type = HEX_TYPE_STRINGS[key];
@@ -562,13 +353,13 @@ impl Renderer for Sprite {
                     }
                     // random color
                     let color_diff_range = 0.04;
-                    let color_diff = if self.random_color.is_hextype_included(&hex_type) {
+                    let color_diff = if self.textures.random_color.is_hextype_included(&hex_type) {
                         rng.gen_range(1.0 - color_diff_range, 1.0 + color_diff_range)
                     } else {
                         1.0
                     };
                     // random rotation
-                    let rotation = if self.random_rotation.is_hextype_included(&hex_type) {
+                    let rotation = if self.textures.random_rotation.is_hextype_included(&hex_type) {
                         Sprite::get_rotation(rng.gen_range::<u32, u32, u32>(0,5))
                     } else {
                         Sprite::get_rotation(0)
@@ -586,7 +377,7 @@ impl Renderer for Sprite {
                     }
                     Some(vec)
                 }).flatten().collect::<Vec<_>>();
-                let v_buffer = vertex::VertexBuffer::new(&self.headless, &data).unwrap();
+                let v_buffer = vertex::VertexBuffer::new(&self.headless, &data).map_err(RenderError::open_gl)?;
                 if i == 1 {
                     instances.insert(key.to_string(), v_buffer);
                 } else {
@@ -595,16 +386,16 @@ impl Renderer for Sprite {
             }
         }
         
-        if let Setting::None = &self.render_in_25d {} else {
+        if let Setting::None = &self.textures.render_in_25d {} else {
             for key in HEX_TYPE_STRINGS.keys() {
                 let colors = map.field.iter().filter_map(|hex| {
                     if hex.terrain_type != HEX_TYPE_STRINGS[*key] {
                         return None
                     }
-                    Some(rng.gen_range::<u32, u32, u32>(1, self.variations_cover[*key] + 1))
+                    Some(rng.gen_range::<u32, u32, u32>(1, self.textures.variations_cover[*key] + 1))
                 }).collect::<Vec<u32>>();
-                if self.render_in_25d.is_hextype_included(&HEX_TYPE_STRINGS[*key]) {
-                    for i in 1..=self.variations[*key] {
+                if self.textures.render_in_25d.is_hextype_included(&HEX_TYPE_STRINGS[*key]) {
+                    for i in 1..=self.textures.variations[*key] {
                         let mut colors_iter = colors.iter();
                         let data = map.field.iter().filter_map(|hex| {
                             let hex_type = HEX_TYPE_STRINGS[*key];
@@ -616,7 +407,7 @@ impl Renderer for Sprite {
                             }
                             // random color
                             let color_diff_range = 0.04;
-                            let color_diff = if self.random_color.is_hextype_included(&hex_type) {
+                            let color_diff = if self.textures.random_color.is_hextype_included(&hex_type) {
                                 rng.gen_range(1.0 - color_diff_range, 1.0 + color_diff_range)
                             } else {
                                 1.0
@@ -636,7 +427,7 @@ impl Renderer for Sprite {
                             }
                             Some(vec)
                         }).flatten().collect::<Vec<_>>();
-                        let v_buffer = vertex::VertexBuffer::new(&self.headless, &data).unwrap();
+                        let v_buffer = vertex::VertexBuffer::new(&self.headless, &data).map_err(RenderError::open_gl)?;
                         if i == 1 {
                             instances_cover.insert(key.to_string(), v_buffer);
                         } else {
@@ -649,9 +440,9 @@ impl Renderer for Sprite {
         
         let scale = self.multiplier / self.tile_size as f32 * 2.0;
 
-        let texture = Texture2d::empty(&self.headless, 1024, 1024).unwrap();
-        let depth = glium::texture::DepthTexture2d::empty(&self.headless, 1024, 1024).unwrap();
-        let mut target = framebuffer::SimpleFrameBuffer::with_depth_buffer(&self.headless, &texture, &depth).unwrap();
+        let texture = Texture2d::empty(&self.headless, 1024, 1024).map_err(RenderError::open_gl)?;
+        let depth = glium::texture::DepthTexture2d::empty(&self.headless, 1024, 1024).map_err(RenderError::open_gl)?;
+        let mut target = framebuffer::SimpleFrameBuffer::with_depth_buffer(&self.headless, &texture, &depth).map_err(RenderError::open_gl)?;
 
         let target_size_x = (map.absolute_size_x * self.multiplier) as usize;
         let target_size_y = (map.absolute_size_y * self.multiplier) as usize;
@@ -672,8 +463,8 @@ impl Renderer for Sprite {
 
                 // render debug hexes
                 let uniforms = uniform!{transform: transform};
-                target.draw((&vertex_buffer, instances_debug.per_instance().unwrap()),
-                    &indices, &self.program_debug, &uniforms, &Default::default()).unwrap();
+                target.draw((&vertex_buffer, instances_debug.per_instance().map_err(|_| RenderError::instancing())?),
+                    indices, &self.program_debug, &uniforms, &Default::default()).map_err(RenderError::open_gl)?;
 
                 // render hexes
                 for key in instances.keys() {
@@ -687,8 +478,8 @@ impl Renderer for Sprite {
                         transform: transform,
                         tex: &textures[substrings[0]][index],
                     };
-                    target.draw((&vertex_buffer, instances[key].per_instance().unwrap()),
-                        &indices, &self.program, &uniforms, &Default::default()).unwrap();
+                    target.draw((&vertex_buffer, instances[key].per_instance().map_err(|_| RenderError::instancing())?),
+                        indices, &self.program, &uniforms, &Default::default()).map_err(RenderError::open_gl)?;
                 }
                 // render 2.5d hexes
                 for key in instances_cover.keys() {
@@ -710,17 +501,17 @@ impl Renderer for Sprite {
                         },
                         ..Default::default()
                     };
-                    target.draw((&vertex_buffer_cover, instances_cover[key].per_instance().unwrap()),
-                        &indices, &self.program_cover, &uniforms, &params).unwrap();
+                    target.draw((&vertex_buffer_cover, instances_cover[key].per_instance().map_err(|_| RenderError::instancing())?),
+                        indices, &self.program_cover, &uniforms, &params).map_err(RenderError::open_gl)?;
                 }
 
                 // reading the front buffer into an image
                 let image: texture::RawImage2d<u8> = texture.read();
-                Self::add_tile_to_image(&image.data, &mut final_image_buffer, map, self.multiplier, 1024, x as usize, y as usize);
+                Self::try_add_tile_to_image(&image.data, &mut final_image_buffer, map, self.multiplier, 1024, x as usize, y as usize)?;
             }
         }
 
-        Image::from_buffer(target_size_x as u32, target_size_y as u32, final_image_buffer, ColorMode::Rgba)
+        Ok(Image::from_buffer(target_size_x as u32, target_size_y as u32, final_image_buffer, ColorMode::Rgba))
     }
 
     fn set_scale(&mut self, scale: f32) {
@@ -739,40 +530,7 @@ impl Renderer for Sprite {
 
 impl Default for Sprite {
     fn default() -> Sprite {
-        // try to construct window
-        let tile_size = 1024;
-
-        let (headless, event_loop) = Self::create_display(tile_size).unwrap();
-        let (program, program_cover, program_debug) = Self::create_programs(&headless);
-
-        let mut empty_variations = HashMap::new();
-        let mut empty_variations_cover = HashMap::new();
-        let textures = HashMap::new();
-        let textures_cover = HashMap::new();
-        for hextype in HEX_TYPE_STRINGS.keys() {
-            empty_variations.insert((**hextype).to_owned(), 1);
-            empty_variations_cover.insert((**hextype).to_owned(), 1);
-        }
-        let mut ren = Sprite{
-            multiplier: 50.0,
-            wrap_map: true,
-            texture_folder: None,
-            random_color: Setting::None,
-            random_rotation: Setting::All,
-            render_in_25d: Setting::None,
-            variations: empty_variations,
-            variations_cover: empty_variations_cover,
-            tile_size,
-            textures,
-            textures_cover,
-            _event_loop: event_loop,
-            program,
-            program_cover,
-            program_debug,
-            headless
-        };
-        ren.load_texture_data();
-        ren
+        Sprite::try_new().unwrap()
     }
 }
 
@@ -782,49 +540,10 @@ struct Vertex {
     tex_coords: [f32; 2],
 }
 
+implement_vertex!(Vertex, position, tex_coords);
+
 impl Vertex {
     pub fn from_tupples(coords: (f32, f32, f32), tex_coords: (f32, f32)) -> Vertex {
         Vertex{position: [coords.0, coords.1, coords.2], tex_coords: [tex_coords.0, tex_coords.1]}
     }
 }
-
-/// Enum used in Sprite renderer
-#[derive(Debug, Clone)]
-pub enum Setting {
-    All,
-    None,
-    Some(Vec<HexType>)
-}
-
-impl Setting {
-    fn parse_array(arr: &[Value]) -> Setting {
-        let mut types: Vec<HexType> = Vec::new();
-        for val in arr {
-            if let Value::String(string) = val {
-                let result = HEX_TYPE_STRINGS.get(string.as_str());
-                if let Some(hextype) = result {
-                    types.push(*hextype)
-                }
-            }
-        }
-        if types.is_empty() {
-            return Setting::None;
-        }
-        Setting::Some(types)
-    }
-
-    fn is_hextype_included (&self, hex_type: &HexType) -> bool {
-        match self {
-            Setting::None => false,
-            Setting::All => true,
-            Setting::Some(types) => {
-                for h_type in types {
-                    if h_type == hex_type {
-                        return true
-                    }
-                }
-                false
-            }
-        }
-    }
-}
@@ -0,0 +1,306 @@
+use rand::prelude::*;
+
+use std::f32;
+
+use enigmap::{HexMap, HexType, RATIO};
+
+use crate::renderers::{Image, Renderer, RenderError, ColorMode, get_hex_index};
+use crate::renderers::blend::closest_hex;
+use crate::renderers::textures::{TextureSet, Setting};
+
+/// Maximal random change of texture brightness
+const COLOR_DIFF_RANGE: f32 = 0.04;
+
+/// Textured software renderer
+///
+/// Uses the same texture folders and "settings.toml" as `Sprite`, but renders on CPU, so it does not need OpenGL or GPU.
+/// Textures span `RATIO` hex widths on both axes and are centered on the hex, cover textures are twice as high
+/// and their bottom is aligned with the bottom of the hex.
+/// ## Usage
+/// ```
+/// use enigmap::{HexMap, HexType};
+/// use enigmap_renderer::renderers::{Textured, Renderer, Setting};
+///
+/// let mut renderer = Textured::from_folder("./examples/textures");
+/// renderer.set_random_color(Setting::Some(vec![HexType::Water, HexType::Ocean]));
+/// renderer.set_scale(20.0);
+/// let image = renderer.render(&HexMap::new(10, 10));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Textured {
+    /// Size of `Hex` on X axis in pixels
+    multiplier: f32,
+    /// Should the map repeat on the X axis
+    wrap_map: bool,
+    /// Textures and their settings
+    textures: TextureSet,
+}
+
+/// Randomly chosen look of single hex
+struct HexLook {
+    variation: usize,
+    cover_variation: usize,
+    /// Cosine and sine of texture rotation
+    rotation: (f32, f32),
+    color_diff: f32,
+}
+
+impl Textured {
+    /// Creates new instance of Textured using specified folder as a source of textures
+    ///
+    /// Textures must be in a png format and be named specificaly, e.g., "Forest.png".
+    /// If the path is invalid default settings will be used.
+    /// # Panics
+    /// when texture can't be decoded
+    pub fn from_folder(folder: &str) -> Textured {
+        Self::try_from_folder(folder).unwrap()
+    }
+
+    /// Creates new instance of Textured using specified folder as a source of textures, returns error when texture can't be decoded
+    pub fn try_from_folder(folder: &str) -> Result<Textured, RenderError> {
+        Ok(Textured{textures: TextureSet::from_folder(folder)?, ..Default::default()})
+    }
+
+    /// Sets texture to a specified `HexType`
+    /// Expects RGBA image data
+    /// Won't overwrite variations
+    pub fn set_texture(&mut self, image_data: &[u8], width: u32, height: u32, tile: HexType, is_cover: bool) {
+        self.textures.set_texture(image_data, width, height, tile, is_cover);
+    }
+
+    /// Adds texture variation to a specified `HexType`
+    /// Expects RGBA image data
+    pub fn add_texture_variation(&mut self, image_data: &[u8], width: u32, height: u32, tile: HexType, is_cover: bool) {
+        self.textures.add_texture_variation(image_data, width, height, tile, is_cover);
+    }
+
+    /// Removes specific variation
+    /// Won't remove anything if index is out of range or there is only one texture present
+    pub fn remove_variation(&mut self, index: usize, tile: HexType, is_cover: bool) -> Option<usize> {
+        self.textures.remove_variation(index, tile, is_cover)
+    }
+
+    /// Enable or disable random tint on specific hexes
+    pub fn set_random_color(&mut self, setting: Setting) {
+        self.textures.random_color = setting;
+    }
+
+    /// Enable or disable random rotation on specific hexes
+    pub fn set_random_rotation(&mut self, setting: Setting) {
+        self.textures.random_rotation = setting;
+    }
+
+    /// Enable or disable 2.5D rendering on specific hexes
+    ///
+    /// Only hexes with loaded cover textures are rendered in 2.5D
+    pub fn set_cover(&mut self, setting: Setting) {
+        self.textures.render_in_25d = setting;
+    }
+
+    /// Chooses variation, rotation and tint of every hex in `HexMap::field`
+    fn looks(&self, map: &HexMap) -> Vec<HexLook> {
+        let mut rng = thread_rng();
+        map.field.iter().map(|hex| {
+            let key = String::from(hex.terrain_type);
+            let variations = self.textures.textures.get(&key).map_or(1, |textures| textures.len());
+            let cover_variations = self.textures.textures_cover.get(&key).map_or(1, |textures| textures.len());
+            let color_diff = if self.textures.random_color.is_hextype_included(&hex.terrain_type) {
+                rng.gen_range(1.0 - COLOR_DIFF_RANGE, 1.0 + COLOR_DIFF_RANGE)
+            } else {
+                1.0
+            };
+            let angle = if self.textures.random_rotation.is_hextype_included(&hex.terrain_type) {
+                rng.gen_range(0, 6) as f32 / 6.0 * (f32::consts::PI * 2.0)
+            } else {
+                0.0
+            };
+            HexLook{
+                variation: rng.gen_range(0, variations.max(1)),
+                cover_variation: rng.gen_range(0, cover_variations.max(1)),
+                rotation: (angle.cos(), angle.sin()),
+                color_diff
+            }
+        }).collect()
+    }
+
+    /// Fills every pixel with texture of the hex, which contains it
+    fn render_hexes(&self, map: &HexMap, looks: &[HexLook], image: &mut Image) {
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                let point = ((x as f32 + 0.5) / self.multiplier, (y as f32 + 0.5) / self.multiplier);
+                let (hex, _) = match closest_hex(map, point, self.wrap_map) {
+                    Some(closest) => closest,
+                    None => continue
+                };
+                let center = hex.center();
+                let offset = (point.0 - center.0, point.1 - center.1);
+                // leave zigzag map edges empty
+                if offset.1.abs() > RATIO / 2.0 * (1.0 - offset.0.abs()) {
+                    continue;
+                }
+                let index = match get_hex_index(map, hex.x, hex.y, self.wrap_map) {
+                    Some(index) => index,
+                    None => continue
+                };
+                let look = &looks[index];
+                let color = match map.field[index].terrain_type {
+                    HexType::Debug(r, g, b) => [r, g, b],
+                    hex_type => {
+                        let texture = match self.textures.textures.get(&String::from(hex_type)) {
+                            Some(textures) => &textures[look.variation],
+                            None => continue
+                        };
+                        let (cos, sin) = look.rotation;
+                        let rotated = (offset.0 * cos - offset.1 * sin, offset.0 * sin + offset.1 * cos);
+                        tint(sample(texture, rotated.0 / RATIO + 0.5, rotated.1 / RATIO + 0.5), look.color_diff)
+                    }
+                };
+                image.put_pixel_rgba(x, y, [color[0], color[1], color[2], 255]);
+            }
+        }
+    }
+
+    /// Draws covers of 2.5D hexes from the top row, so lower hexes overlap higher ones
+    fn render_covers(&self, map: &HexMap, looks: &[HexLook], image: &mut Image) {
+        let offsets: &[f32] = if self.wrap_map { &[-1.0, 0.0, 1.0] } else { &[0.0] };
+        for (hex, look) in map.field.iter().zip(looks) {
+            if !self.textures.render_in_25d.is_hextype_included(&hex.terrain_type) {
+                continue;
+            }
+            let texture = match self.textures.textures_cover.get(&String::from(hex.terrain_type)) {
+                Some(textures) => &textures[look.cover_variation],
+                None => continue
+            };
+            for offset in offsets {
+                let center = hex.center();
+                let center = (center.0 + offset * map.size_x as f32, center.1);
+                let min_x = ((center.0 - 0.5) * self.multiplier).max(0.0) as u32;
+                let max_x = (((center.0 + 0.5) * self.multiplier).ceil().max(0.0) as u32).min(image.width());
+                let min_y = ((center.1 - RATIO * 1.5) * self.multiplier).max(0.0) as u32;
+                let max_y = (((center.1 + RATIO / 2.0) * self.multiplier).ceil().max(0.0) as u32).min(image.height());
+                for y in min_y..max_y {
+                    for x in min_x..max_x {
+                        let point = ((x as f32 + 0.5) / self.multiplier, (y as f32 + 0.5) / self.multiplier);
+                        let offset = (point.0 - center.0, point.1 - center.1);
+                        // hex stretched up by its height
+                        let slope = RATIO / 2.0 * offset.0.abs();
+                        if offset.0.abs() > 0.5 || offset.1 > RATIO / 2.0 - slope || offset.1 < slope - RATIO * 1.5 {
+                            continue;
+                        }
+                        let color = sample(texture, offset.0 / RATIO + 0.5, (offset.1 + RATIO * 1.5) / (RATIO * 2.0));
+                        if color[3] < 128.0 {
+                            continue;
+                        }
+                        let color = tint(color, look.color_diff);
+                        image.put_pixel_rgba(x, y, [color[0], color[1], color[2], 255]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for Textured {
+    type Output = Image;
+
+    fn render(&self, map: &HexMap) -> Image {
+        let width = (map.absolute_size_x * self.multiplier) as u32;
+        let height = (map.absolute_size_y * self.multiplier) as u32;
+        let mut image = Image::from_buffer(width, height, [0, 0, 0, 255].repeat((width * height) as usize), ColorMode::Rgba);
+
+        let looks = self.looks(map);
+        self.render_hexes(map, &looks, &mut image);
+        self.render_covers(map, &looks, &mut image);
+        image
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        if scale > 1.0 {
+            self.multiplier = scale;
+        } else {
+            self.multiplier = 50.0;
+            eprintln!("Tried to set invalid scale, setting default scale instead.");
+        }
+    }
+
+    fn set_wrap_map(&mut self, value: bool) {
+        self.wrap_map = value;
+    }
+}
+
+impl Default for Textured {
+    fn default() -> Textured {
+        Textured{
+            multiplier: 50.0,
+            wrap_map: true,
+            textures: TextureSet::default()
+        }
+    }
+}
+
+/// Returns bilinearly filtered RGBA color on relative texture coordinates, clamped to texture edges
+fn sample(texture: &Image, u: f32, v: f32) -> [f32; 4] {
+    let (width, height) = (texture.width(), texture.height());
+    let x = (u * width as f32 - 0.5).max(0.0).min((width - 1) as f32);
+    let y = (v * height as f32 - 0.5).max(0.0).min((height - 1) as f32);
+    let (x0, y0) = (x as u32, y as u32);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fraction_x, fraction_y) = (x - x0 as f32, y - y0 as f32);
+
+    let mut color = [0.0; 4];
+    let corners = [
+        (x0, y0, (1.0 - fraction_x) * (1.0 - fraction_y)),
+        (x1, y0, fraction_x * (1.0 - fraction_y)),
+        (x0, y1, (1.0 - fraction_x) * fraction_y),
+        (x1, y1, fraction_x * fraction_y)
+    ];
+    for &(x, y, weight) in &corners {
+        for (channel, value) in color.iter_mut().zip(texture.get_pixel(x, y)) {
+            *channel += f32::from(*value) * weight;
+        }
+    }
+    color
+}
+
+fn tint(color: [f32; 4], color_diff: f32) -> [u8; 3] {
+    let channel = |value: f32| (value * color_diff).round().min(255.0) as u8;
+    [channel(color[0]), channel(color[1]), channel(color[2])]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enigmap::Hex;
+
+    #[test]
+    fn renders_textures_without_gpu() {
+        let mut map = HexMap::new(10, 10);
+        map.fill(HexType::Field);
+        map.field[22].terrain_type = HexType::Debug(10, 20, 30);
+        map.field[44].terrain_type = HexType::Forest;
+
+        let mut renderer = Textured::default();
+        renderer.set_scale(20.0);
+        renderer.set_texture(&[200, 100, 50, 255].repeat(4), 2, 2, HexType::Field, false);
+        let image = renderer.render(&map);
+        assert_eq!((map.absolute_size_x * 20.0) as u32, image.width());
+
+        let pixel = |hex: &Hex| {
+            let (x, y) = hex.center();
+            image.get_pixel((x * 20.0) as u32, (y * 20.0) as u32).to_vec()
+        };
+        assert_eq!(vec![200, 100, 50, 255], pixel(&map.field[0]));
+        assert_eq!(vec![10, 20, 30, 255], pixel(&map.field[22]));
+        // error texture is black and magenta
+        assert!(pixel(&map.field[44])[1] == 0);
+
+        // settings and textures from folder
+        let renderer = Textured::from_folder("./examples/textures");
+        assert!(renderer.textures.random_color.is_hextype_included(&HexType::Ocean));
+        assert!(renderer.textures.render_in_25d.is_hextype_included(&HexType::Forest));
+        assert_eq!(2, renderer.textures.textures["Field"].len());
+        assert_eq!(2, renderer.textures.textures_cover["Forest"].len());
+        assert_eq!(1, renderer.textures.textures["Ocean"].len());
+        renderer.render(&map);
+    }
+}
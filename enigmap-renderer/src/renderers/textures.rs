@@ -0,0 +1,327 @@
+use png::{Decoder, ColorType, Transformations};
+use toml::Value;
+
+use std::path::Path;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::collections::HashMap;
+
+use enigmap::{HexType, HEX_TYPE_STRINGS};
+
+use crate::renderers::{Image, ColorMode, RenderError};
+
+/// Textures and settings of textured renderers
+///
+/// Folder contains png textures named by `HexType`, e.g., "Forest.png", variations with index suffix, e.g., "Forest_1.png",
+/// covers with "_cover" suffix, e.g., "Forest_cover.png" and "Forest_cover_1.png", and optional "settings.toml".
+#[derive(Debug, Clone)]
+pub(crate) struct TextureSet {
+    /// Path to folder with textures
+    pub texture_folder: Option<String>,
+    pub random_rotation: Setting,
+    pub random_color: Setting,
+    pub render_in_25d: Setting,
+    pub variations: HashMap<String, u32>,
+    pub variations_cover: HashMap<String, u32>,
+    pub textures: HashMap<String, Vec<Image>>,
+    pub textures_cover: HashMap<String, Vec<Image>>,
+}
+
+impl TextureSet {
+    /// Loads textures and settings from folder
+    ///
+    /// If the path is invalid default settings and error textures will be used, returns error when texture can't be decoded.
+    pub fn from_folder(folder: &str) -> Result<TextureSet, RenderError> {
+        let mut set = Self::empty();
+        set.texture_folder = Some(folder.to_string());
+        // check for path
+        if !Path::new(folder).exists() {
+            println!("WARNING! Path does not exist, renderer will use blank textures.");
+            set.texture_folder = None;
+        } else {
+            set.load_settings(folder);
+        }
+        set.load_texture_data()?;
+        Ok(set)
+    }
+
+    fn empty() -> TextureSet {
+        let mut variations = HashMap::new();
+        let mut variations_cover = HashMap::new();
+        for hextype in HEX_TYPE_STRINGS.keys() {
+            variations.insert((**hextype).to_owned(), 1);
+            variations_cover.insert((**hextype).to_owned(), 1);
+        }
+        TextureSet{
+            texture_folder: None,
+            random_rotation: Setting::All,
+            random_color: Setting::None,
+            render_in_25d: Setting::None,
+            variations,
+            variations_cover,
+            textures: HashMap::new(),
+            textures_cover: HashMap::new()
+        }
+    }
+
+    fn load_settings(&mut self, folder: &str) {
+        // check for settings file
+        let mut file = match File::open(folder.to_owned() + "/settings.toml") {
+            Ok(file) => file,
+            Err(error) => {
+                // don't write warning when file is non-existent
+                if error.kind() != ErrorKind::NotFound {
+                    println!("WARNING! Error when opening settings file. Renderer will use default settings.");
+                }
+                return;
+            }
+        };
+        let mut settings = String::new();
+        // load contents and handle errors
+        if file.read_to_string(&mut settings).is_err() {
+            println!("WARNING! Error when reading settings file. Renderer will use default settings.");
+            return;
+        }
+        let settings = match settings.parse::<Value>() {
+            Ok(settings) => settings,
+            Err(_) => {
+                println!("WARNING! Invalid settings file. Renderer will use default settings.");
+                return;
+            }
+        };
+
+        // parse results
+        if let Some(val) = Setting::parse(settings.get("random_rotation")) {
+            self.random_rotation = val;
+        }
+        if let Some(val) = Setting::parse(settings.get("random_color")) {
+            self.random_color = val;
+        }
+        if let Some(val) = Setting::parse(settings.get("render_in_25d")) {
+            self.render_in_25d = val;
+        }
+        parse_variations(settings.get("variations"), &mut self.variations);
+        parse_variations(settings.get("variations_cover"), &mut self.variations_cover);
+    }
+
+    /// Sets texture to a specified `HexType`
+    pub fn set_texture(&mut self, image_data: &[u8], width: u32, height: u32, tile: HexType, is_cover: bool) {
+        if image_data.len() != (width * height * 4) as usize {
+            eprintln!("Warning, image buffer length different than expected");
+        }
+        let key = String::from(tile);
+        let image = Image::from_buffer(width, height, image_data.to_owned(), ColorMode::Rgba);
+        if is_cover {
+            self.textures_cover.get_mut(&key).unwrap()[0] = image;
+        } else {
+            self.textures.get_mut(&key).unwrap()[0] = image;
+        }
+    }
+
+    /// Adds texture variation to a specified `HexType`
+    pub fn add_texture_variation(&mut self, image_data: &[u8], width: u32, height: u32, tile: HexType, is_cover: bool) {
+        if image_data.len() != (width * height * 4) as usize {
+            eprintln!("Warning, image buffer length different than expected");
+        }
+        let key = String::from(tile);
+        let image = Image::from_buffer(width, height, image_data.to_owned(), ColorMode::Rgba);
+        if is_cover {
+            self.textures_cover.get_mut(&key).unwrap().push(image);
+            *self.variations_cover.get_mut(&key).unwrap() += 1;
+        } else {
+            self.textures.get_mut(&key).unwrap().push(image);
+            *self.variations.get_mut(&key).unwrap() += 1;
+        }
+    }
+
+    /// Removes specific variation
+    pub fn remove_variation(&mut self, index: usize, tile: HexType, is_cover: bool) -> Option<usize> {
+        let key = String::from(tile);
+        if is_cover {
+            if self.variations_cover[&key] == 1 || index as u32 > self.variations_cover[&key] {
+                return None;
+            }
+            self.textures_cover.get_mut(&key).unwrap().remove(index);
+            Some(index)
+        } else {
+            if self.variations[&key] == 1 || index as u32 > self.variations[&key] {
+                return None;
+            }
+            self.textures.get_mut(&key).unwrap().remove(index);
+            Some(index)
+        }
+    }
+
+    fn load_texture_data(&mut self) -> Result<(), RenderError> {
+        for key in HEX_TYPE_STRINGS.keys() {
+            if !self.textures.contains_key(*key) {
+                self.textures.insert((**key).to_owned(), Vec::new());
+            }
+            let texture = self.texture_from_path(&format!("/{}.png", key))?;
+            self.textures.get_mut(*key).unwrap().push(texture);
+            // check for alternative textures
+            let max_textures = self.variations[*key];
+            for i in 1..max_textures {
+                let texture = self.texture_from_path(&format!("/{}_{}.png", key, i))?;
+                self.textures.get_mut(*key).unwrap().push(texture);
+            }
+        }
+        // load cover textures
+        for key in HEX_TYPE_STRINGS.keys() {
+            if !self.render_in_25d.is_hextype_included(&HEX_TYPE_STRINGS[*key]) {
+                continue;
+            }
+            if !self.textures_cover.contains_key(*key) {
+                self.textures_cover.insert((**key).to_owned(), Vec::new());
+            }
+            let texture = self.texture_from_path(&format!("/{}_cover.png", key))?;
+            self.textures_cover.get_mut(*key).unwrap().push(texture);
+            // check for alternative textures
+            let max_textures = self.variations_cover[*key];
+            for i in 1..max_textures {
+                let texture = self.texture_from_path(&format!("/{}_cover_{}.png", key, i))?;
+                self.textures_cover.get_mut(*key).unwrap().push(texture);
+            }
+        }
+        Ok(())
+    }
+
+    fn generate_error_texture() -> Image {
+        Image::from_fn_rgba(32, 32, |x, y| {
+            // create checkerboard
+            let odd_x = (x/4) % 2 == 1;
+            let odd_y = (y/4) % 2 == 1;
+            if (odd_x && !odd_y) || (!odd_x && odd_y) {
+                [0, 0, 0, 255]
+            } else {
+                [255, 0, 255, 255]
+            }
+        })
+    }
+
+    /// Loads RGBA texture, if texture folder is not specified or texture is missing, uses error texture instead
+    ///
+    /// 16-bit textures are reduced to 8 bits, palettes are expanded to RGB
+    fn texture_from_path(&self, path: &str) -> Result<Image, RenderError> {
+        let folder = match &self.texture_folder {
+            Some(folder) => folder,
+            None => return Ok(Self::generate_error_texture())
+        };
+        let file = match File::open(folder.to_owned() + path) {
+            Ok(file) => file,
+            Err(_err) => {
+                eprintln!("texture {} not found", path);
+                return Ok(Self::generate_error_texture());
+            }
+        };
+        let texture_error = |message: String| RenderError::Texture{path: folder.to_owned() + path, message};
+        let mut decoder = Decoder::new(file);
+        decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+        let (info, mut reader) = decoder.read_info().map_err(|error| texture_error(error.to_string()))?;
+        let mut buf = vec![0; info.buffer_size()];
+        // Read the next frame. Currently this function should only called once.
+        reader.next_frame(&mut buf).map_err(|error| texture_error(error.to_string()))?;
+        match info.color_type {
+            ColorType::RGBA => Ok(Image::from_buffer(info.width, info.height, buf, ColorMode::Rgba)),
+            ColorType::RGB => {
+                let buf = buf.chunks(3).flat_map(|pixel| vec![pixel[0], pixel[1], pixel[2], 255]).collect();
+                Ok(Image::from_buffer(info.width, info.height, buf, ColorMode::Rgba))
+            },
+            color_type => Err(texture_error(format!("unsupported color type {:?}, expected RGB or RGBA", color_type)))
+        }
+    }
+}
+
+impl Default for TextureSet {
+    fn default() -> TextureSet {
+        let mut set = Self::empty();
+        // only error textures are generated without texture folder
+        set.load_texture_data().unwrap();
+        set
+    }
+}
+
+fn parse_variations(value: Option<&Value>, variations: &mut HashMap<String, u32>) {
+    // check if it is table
+    if let Some(Value::Table(table)) = value {
+        // iterate over all hextpyes
+        for (hextype, variations) in variations.iter_mut() {
+            // check if value in table is a number
+            if let Some(Value::Integer(int)) = table.get(hextype) {
+                *variations = (*int).max(1) as u32;
+            }
+        }
+    }
+}
+
+/// Enum used in textured renderers
+#[derive(Debug, Clone)]
+pub enum Setting {
+    All,
+    None,
+    Some(Vec<HexType>)
+}
+
+impl Setting {
+    fn parse(value: Option<&Value>) -> Option<Setting> {
+        match value? {
+            Value::Array(arr) => Some(Setting::parse_array(arr)),
+            Value::Boolean(boo) => {
+                if *boo {
+                    Some(Setting::All)
+                } else {
+                    Some(Setting::None)
+                }
+            }
+            _ => None
+        }
+    }
+
+    fn parse_array(arr: &[Value]) -> Setting {
+        let mut types: Vec<HexType> = Vec::new();
+        for val in arr {
+            if let Value::String(string) = val {
+                let result = HEX_TYPE_STRINGS.get(string.as_str());
+                if let Some(hextype) = result {
+                    types.push(*hextype)
+                }
+            }
+        }
+        if types.is_empty() {
+            return Setting::None;
+        }
+        Setting::Some(types)
+    }
+
+    pub(crate) fn is_hextype_included(&self, hex_type: &HexType) -> bool {
+        match self {
+            Setting::None => false,
+            Setting::All => true,
+            Setting::Some(types) => types.contains(hex_type)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sixteen_bit_texture() {
+        let folder = std::env::temp_dir().join("enigmap_textures_16");
+        std::fs::create_dir_all(&folder).unwrap();
+        {
+            let file = File::create(folder.join("Forest.png")).unwrap();
+            let mut encoder = png::Encoder::new(file, 1, 1);
+            encoder.set_color(ColorType::RGB);
+            encoder.set_depth(png::BitDepth::Sixteen);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[0x20, 0x00, 0x40, 0x00, 0x60, 0x00]).unwrap();
+        }
+
+        let set = TextureSet::from_folder(folder.to_str().unwrap());
+        std::fs::remove_dir_all(&folder).unwrap();
+        let texture = &set.unwrap().textures["Forest"][0];
+        assert_eq!(&[0x20, 0x40, 0x60, 255], texture.get_pixel(0, 0));
+    }
+}
@@ -1,12 +1,15 @@
-use enigmap::{HexMap, Hex, HexType, RATIO};
+use enigmap::{HexMap, Hex, HexType, Decor, RATIO};
 
-use crate::renderers::{Renderer, colors::ColorMap, get_hex_vertex};
+use crate::renderers::{Renderer, RenderError, CoordinateFormat, DecorStyle, Labels, Legend, colors::ColorMap, get_hex_vertex};
+use crate::renderers::decor::{self, Polygon, SETTLEMENTS};
+use crate::renderers::regions::find_regions;
+use crate::renderers::labels::PlacedLabel;
 
 use rand::prelude::*;
 
 use svg::Document;
 use svg::node::Node;
-use svg::node::element::{Definitions, Group, Path, Use};
+use svg::node::element::{Definitions, Group, Path, Rectangle, Symbol, Text, Use};
 
 /// Vector renderer
 /// 
@@ -16,7 +19,15 @@ pub struct Vector {
     randomize_colors: bool,
     colors: ColorMap,
     scale: f32,
+    render_decor: bool,
+    merge_regions: bool,
     pub use_xlink: bool,
+    /// Style of rendered decor
+    pub decor_style: DecorStyle,
+    /// Coordinate and user labels
+    pub labels: Labels,
+    /// Legend panel appended under the map
+    pub legend: Legend,
 }
 
 impl Vector {
@@ -96,17 +107,273 @@ impl Vector {
         self.scale = scale;
     }
 
+    /// Should neighbouring hexes with the same `HexType` be merged into one `<path>`
+    ///
+    /// Produces much smaller files without seams between hexes, colors are randomized per region
+    pub fn set_merge_regions(&mut self, value: bool) {
+        self.merge_regions = value;
+    }
+
+    /// Should `Hex::decor` be rendered with `decor_style`
+    pub fn set_render_decor(&mut self, value: bool) {
+        self.render_decor = value;
+    }
+
     /// Choose if the svg output should use `xlink:href` or `href` for linking objects.
     ///
     /// `xlink:href` was deprecated in SVG 2.0, so this exists as a backward compatibility option
     pub fn set_use_xlink(&mut self, use_xlink: bool) {
         self.use_xlink = use_xlink;
     }
+
+    fn link(&self, node: &mut Use, id: &str) {
+        if self.use_xlink {
+            node.assign("xlink:href", format!("#{}", id));
+        } else {
+            node.assign("href", format!("#{}", id));
+        }
+    }
+
+    /// Adds symbol for every decor icon into definitions
+    fn decor_symbols(&self, defs: &mut Definitions) {
+        for &flag in &[Decor::HILL, Decor::RUIN, Decor::VILLAGE, Decor::CITY] {
+            let (parts, color) = self.decor_style.icon(flag, (0.0, 0.0));
+            let mut path = Path::new();
+            path.assign("d", self.polygon_path(&parts));
+            path.assign("fill", hex_color(color));
+
+            let mut symbol = Symbol::new();
+            symbol.assign("id", icon_id(flag));
+            symbol.assign("overflow", "visible");
+            symbol.append(path);
+            defs.append(symbol);
+        }
+    }
+
+    fn polygon_path(&self, polygons: &[Polygon]) -> String {
+        let mut path = String::new();
+        for polygon in polygons {
+            for (index, point) in polygon.iter().enumerate() {
+                let command = if index == 0 { 'M' } else { 'L' };
+                path += format!("{}{:.3} {:.3} ", command, point.0 * self.scale, point.1 * self.scale).as_str();
+            }
+            path += "Z ";
+        }
+        path
+    }
+
+    /// Returns layer with one `<use>` for every hex
+    fn hex_layer(&self, map: &HexMap, colors: &[[u8;3]]) -> Group {
+        let mut terrain = layer("terrain");
+
+        for (index, hex) in map.field.iter().enumerate() {
+            let center = hex.center();
+
+            let mut hex = Use::new();
+            self.link(&mut hex, "h");
+            hex.assign("fill", hex_color(colors[index]));
+            hex.assign("x", format!("{}", center.0 * self.scale));
+            hex.assign("y", format!("{:.3}", center.1 * self.scale));
+
+            terrain.append(hex);
+        }
+
+        if self.wrap_map {
+            for (index, hex) in map.field.iter().enumerate() {
+                // discard all hexes that won't be wrapped
+                let wrapping = if index as u32 % map.size_x == 0 && (index as u32 / map.size_x % 2 == 0) {
+                    Wrapping::Right
+                } else if index as u32 % map.size_x == (map.size_x - 1) && (index as u32 / map.size_x % 2 == 1) {
+                    Wrapping::Left
+                } else {
+                    continue
+                };
+
+                let center = hex.center();
+
+                let mut hex = Use::new();
+                self.link(&mut hex, "hc");
+                hex.assign("fill", hex_color(colors[index]));
+                let offset = match wrapping {
+                    Wrapping::Left => -(map.size_x as f32) * self.scale,
+                    Wrapping::Right => map.size_x as f32 * self.scale,
+                };
+                hex.assign("x", format!("{}", center.0 * self.scale + offset));
+                hex.assign("y", format!("{:.3}", center.1 * self.scale));
+
+                // rotate halfs if needed
+                if let Wrapping::Right = wrapping {
+                    hex.assign("transform", format!{"rotate(180 {} {})", (center.0 +0.5) * self.scale + offset, (center.1 + RATIO / 2.0) * self.scale});
+                }
+
+                terrain.append(hex);
+            }
+        }
+        terrain
+    }
+
+    /// Returns layer with one path for every region of the same `HexType`
+    fn region_layer(&self, map: &HexMap, colors: &[[u8;3]]) -> Group {
+        let mut terrain = layer("terrain");
+        for region in find_regions(map, self.wrap_map) {
+            let mut path = Path::new();
+            path.assign("d", self.polygon_path(&region.boundaries));
+            path.assign("fill", hex_color(colors[region.hexes[0]]));
+            path.assign("fill-rule", "evenodd");
+
+            // copies over wrapped edge fill the gaps left by shifted rows
+            let column = |index: &usize| map.field[*index].x + map.field[*index].y / 2;
+            let mut copies = Vec::new();
+            if self.wrap_map && region.hexes.iter().any(|index| column(index) == 0) {
+                copies.push(map.size_x as f32 * self.scale);
+            }
+            if self.wrap_map && region.hexes.iter().any(|index| column(index) == map.size_x as i32 - 1) {
+                copies.push(-(map.size_x as f32) * self.scale);
+            }
+            for offset in copies {
+                let mut copy = path.clone();
+                copy.assign("transform", format!("translate({} 0)", offset));
+                terrain.append(copy);
+            }
+            terrain.append(path);
+        }
+        terrain
+    }
+
+    /// Returns layer with smooth line through centers of connected hexes with `flag`
+    fn line_layer(&self, map: &HexMap, flag: Decor, id: &str, width: f32, color: [u8; 3]) -> Group {
+        let point = |point: (f32, f32)| format!("{:.3} {:.3}", point.0 * self.scale, point.1 * self.scale);
+        let mut data = String::new();
+        for hex in map.field.iter().filter(|hex| hex.decor.contains(flag)) {
+            let center = hex.center();
+            let ends = decor::line_segments(map, hex, flag, self.wrap_map).into_iter().map(|(_, end)| end).collect::<Vec<_>>();
+            match ends.len() {
+                0 => continue,
+                1 => data += format!("M{} L{} ", point(center), point(ends[0])).as_str(),
+                _ => {
+                    // curves between edge midpoints are smoothly joined
+                    data += format!("M{} Q{} {} ", point(ends[0]), point(center), point(ends[1])).as_str();
+                    for &end in &ends[2..] {
+                        data += format!("M{} L{} ", point(center), point(end)).as_str();
+                    }
+                }
+            }
+        }
+
+        let mut path = Path::new();
+        path.assign("d", data);
+        path.assign("fill", "none");
+        path.assign("stroke", hex_color(color));
+        path.assign("stroke-width", width * self.scale);
+        path.assign("stroke-linecap", "round");
+        path.assign("stroke-linejoin", "round");
+
+        let mut group = layer(id);
+        group.append(path);
+        group
+    }
+
+    fn label_layer(&self, map: &HexMap) -> Group {
+        let mut group = layer("labels");
+        group.assign("fill", hex_color(self.labels.color));
+        group.assign("font-family", "monospace");
+        group.assign("text-anchor", "middle");
+        group.assign("dominant-baseline", "central");
+        for label in self.labels.layout(map) {
+            group.append(self.text(&label));
+        }
+        group
+    }
+
+    fn text(&self, label: &PlacedLabel) -> Text {
+        let mut text = Text::new();
+        text.assign("x", format!("{:.3}", label.center.0 * self.scale));
+        text.assign("y", format!("{:.3}", label.center.1 * self.scale));
+        text.assign("font-size", format!("{:.3}", label.size * self.scale));
+        text.append(svg::node::Text::new(escape(&label.text)));
+        text
+    }
+
+    /// Returns legend panel placed under the map
+    fn legend_layer(&self, map: &HexMap) -> (Group, f32) {
+        let layout = self.legend.layout(map, &self.colors, map.absolute_size_x);
+        let mut group = layer("legend");
+        group.assign("transform", format!("translate(0 {:.3})", map.absolute_size_y * self.scale));
+        group.assign("font-family", "monospace");
+        group.assign("text-anchor", "middle");
+        group.assign("dominant-baseline", "central");
+
+        let mut background = Rectangle::new();
+        background.assign("width", map.absolute_size_x * self.scale);
+        background.assign("height", layout.height * self.scale);
+        background.assign("fill", hex_color(self.legend.background));
+        group.append(background);
+
+        for (polygon, color) in layout.rectangles {
+            let mut path = Path::new();
+            path.assign("d", self.polygon_path(&[polygon]));
+            path.assign("fill", hex_color(color));
+            group.append(path);
+        }
+        for label in &layout.texts {
+            let mut text = self.text(label);
+            text.assign("fill", hex_color(self.legend.text_color));
+            group.append(text);
+        }
+        (group, layout.height)
+    }
+
+    /// Returns layer with symbol `id` on every hex with one of `flags`
+    fn icon_layer(&self, map: &HexMap, id: &str, flags: &[Decor]) -> Group {
+        let mut group = layer(id);
+        for &flag in flags {
+            for hex in map.field.iter().filter(|hex| hex.decor.contains(flag)) {
+                let (x, mut y) = hex.center();
+                if flag == Decor::HILL {
+                    y += self.decor_style.hill_offset(hex);
+                }
+                let mut icon = Use::new();
+                self.link(&mut icon, icon_id(flag));
+                icon.assign("x", format!("{:.3}", x * self.scale));
+                icon.assign("y", format!("{:.3}", y * self.scale));
+                group.append(icon);
+            }
+        }
+        group
+    }
+}
+
+/// Returns group which is shown as a layer in Inkscape
+fn layer(id: &str) -> Group {
+    let mut group = Group::new();
+    group.assign("id", id);
+    group.assign("inkscape:groupmode", "layer");
+    group.assign("inkscape:label", id);
+    group
+}
+
+/// Returns id of symbol with decor icon
+fn icon_id(flag: Decor) -> &'static str {
+    match flag {
+        Decor::HILL => "hill",
+        Decor::RUIN => "ruin",
+        Decor::VILLAGE => "village",
+        _ => "city"
+    }
+}
+
+/// Escapes characters with special meaning in XML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn hex_color(color: [u8; 3]) -> String {
+    format!("#{:02X}{:02X}{:02X}", color[0], color[1], color[2])
 }
 
 impl Default for Vector {
     fn default() -> Vector {
-        Vector{wrap_map: true, randomize_colors: true, colors: ColorMap::default(), scale: 1.0, use_xlink: false}
+        Vector{wrap_map: true, randomize_colors: true, colors: ColorMap::default(), scale: 1.0, render_decor: true, merge_regions: false, use_xlink: false, decor_style: DecorStyle::default(), labels: Labels::default(), legend: Legend::default()}
     }
 }
 
@@ -115,9 +382,12 @@ impl Renderer for Vector {
 
     fn render(&self, map: &HexMap) -> Document {
         let colors = self.generate_colors(map);
+        let legend = if self.legend.is_enabled() { Some(self.legend_layer(map)) } else { None };
+        let legend_height = legend.as_ref().map_or(0.0, |legend| legend.1);
         let mut doc = Document::new()
             .set("width", map.absolute_size_x * self.scale)
-            .set("height", map.absolute_size_y * self.scale);
+            .set("height", (map.absolute_size_y + legend_height) * self.scale)
+            .set("xmlns:inkscape", "http://www.inkscape.org/namespaces/inkscape");
 
         let mut defs = Definitions::new();
 
@@ -141,69 +411,51 @@ impl Renderer for Vector {
 
         defs.append(hex_group);
 
-        doc.append(defs);
+        if self.render_decor {
+            self.decor_symbols(&mut defs);
+        }
 
-        for (index, hex) in map.field.iter().enumerate() {
-            let color = colors[index];
-            let color = format!("#{:02X}{:02X}{:02X}", color[0], color[1], color[2]);
+        doc.append(defs);
 
-            let center = hex.center();
+        let terrain = if self.merge_regions {
+            self.region_layer(map, &colors)
+        } else {
+            self.hex_layer(map, &colors)
+        };
+        doc.append(terrain);
 
-            let mut hex = Use::new();
-            if self.use_xlink {
-                hex.assign("xlink:href", "#h");
-            } else {
-                hex.assign("href", "#h");
+        if self.render_decor {
+            let style = &self.decor_style;
+            if style.show_hills {
+                doc.append(self.icon_layer(map, "hills", &[Decor::HILL]));
+            }
+            if style.show_rivers {
+                doc.append(self.line_layer(map, Decor::RIVER, "rivers", style.river_width, style.river_color));
+            }
+            if style.show_roads {
+                doc.append(self.line_layer(map, Decor::ROAD, "roads", style.road_width, style.road_color));
+            }
+            if style.show_settlements {
+                doc.append(self.icon_layer(map, "settlements", &SETTLEMENTS));
             }
-            hex.assign("fill", color);
-            hex.assign("x", format!("{}", center.0 * self.scale));
-            hex.assign("y", format!("{:.3}", center.1 * self.scale));
-
-            doc.append(hex);
         }
 
-        if self.wrap_map {
-            for (index, hex) in map.field.iter().enumerate() {
-                // discard all hexes that won't be wrapped
-                let wrapping = if index as u32 % map.size_x == 0 && (index as u32 / map.size_x % 2 == 0) {
-                    Wrapping::Right
-                } else if index as u32 % map.size_x == (map.size_x - 1) && (index as u32 / map.size_x % 2 == 1) {
-                    Wrapping::Left
-                } else {
-                    continue
-                };
-
-                let color = colors[index];
-                let color = format!("#{:02X}{:02X}{:02X}", color[0], color[1], color[2]);
-
-                let center = hex.center();
-
-                let mut hex = Use::new();
-                if self.use_xlink {
-                    hex.assign("xlink:href", "#hc");
-                } else {
-                    hex.assign("href", "#hc");
-                }
-                hex.assign("fill", color);
-                let offset = match wrapping {
-                    Wrapping::Left => -(map.size_x as f32) * self.scale,
-                    Wrapping::Right => map.size_x as f32 * self.scale,
-                };
-                hex.assign("x", format!("{}", center.0 * self.scale + offset));
-                hex.assign("y", format!("{:.3}", center.1 * self.scale));
-
-                // rotate halfs if needed
-                if let Wrapping::Right = wrapping {
-                    hex.assign("transform", format!{"rotate(180 {} {})", (center.0 +0.5) * self.scale + offset, (center.1 + RATIO / 2.0) * self.scale});
-                }
+        if self.labels.coordinates != CoordinateFormat::None || !self.labels.labels.is_empty() {
+            doc.append(self.label_layer(map));
+        }
 
-                doc.append(hex);
-            }
+        if let Some((legend, _)) = legend {
+            doc.append(legend);
         }
-        
+
         doc
     }
 
+    fn try_render(&self, map: &HexMap) -> Result<Document, RenderError> {
+        self.colors.validate(map)?;
+        Ok(self.render(map))
+    }
+
     fn set_scale(&mut self, _scale: f32) {
         unimplemented!();
     }
@@ -218,3 +470,69 @@ enum Wrapping {
     Left,
     Right
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decor_layers() {
+        let mut map = HexMap::new(10, 10);
+        let index = map.coords_to_index(3, 4).unwrap();
+        for hex in &mut map.field[index..index + 3] {
+            hex.terrain_type = HexType::Field;
+            hex.decor.insert(Decor::RIVER);
+        }
+        map.field[index + 1].decor.insert(Decor::CITY);
+
+        let svg = Vector::default().render(&map).to_string();
+        for id in &["id=\"terrain\"", "id=\"rivers\"", "id=\"settlements\"", "<symbol id=\"city\"", "href=\"#city\"", " Q"] {
+            assert!(svg.contains(id), "missing {}", id);
+        }
+        assert_eq!(1, svg.matches("href=\"#city\"").count());
+
+        let mut renderer = Vector::default();
+        renderer.set_render_decor(false);
+        assert!(!renderer.render(&map).to_string().contains("rivers"));
+    }
+
+    #[test]
+    fn merged_regions() {
+        let mut map = HexMap::new(10, 10);
+        map.fill(HexType::Field);
+        let index = map.coords_to_index(3, 4).unwrap();
+        map.field[index].terrain_type = HexType::Water;
+
+        let mut renderer = Vector::default();
+        renderer.set_merge_regions(true);
+        let svg = renderer.render(&map).to_string();
+        assert!(!svg.contains("href=\"#h\""));
+        // lake and field with its copies over wrapped edge
+        assert_eq!(4, svg.matches("fill-rule").count());
+    }
+
+    #[test]
+    fn labels() {
+        let map = HexMap::new(10, 10);
+        let mut renderer = Vector::default();
+        assert!(!renderer.render(&map).to_string().contains("<text"));
+
+        renderer.labels.add(3, 4, "Fish & Chips");
+        let svg = renderer.render(&map).to_string();
+        assert!(svg.contains("id=\"labels\""));
+        assert!(svg.contains("Fish &amp; Chips"));
+    }
+
+    #[test]
+    fn legend() {
+        let mut map = HexMap::new(10, 10);
+        map.field[0].terrain_type = HexType::Desert;
+        let renderer = Vector{legend: Legend{show_terrain: true, scale_bar: Some(2), ..Default::default()}, ..Default::default()};
+        let svg = renderer.render(&map).to_string();
+        assert!(svg.contains("id=\"legend\""));
+        for text in &["Desert", "Water", "2 hexes"] {
+            assert!(svg.contains(text), "missing {}", text);
+        }
+    }
+}
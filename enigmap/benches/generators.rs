@@ -1,7 +1,7 @@
 #[macro_use]
 extern crate criterion;
 
-use criterion::Criterion;
+use criterion::{Criterion, Benchmark};
 
 use enigmap::{
     HexMap,
@@ -35,6 +35,13 @@ fn criterion_benchmark(c: &mut Criterion) {
     map_bench!(c, Circle);
     map_bench!(c, Islands);
     map_bench!(c, Inland);
+
+    // huge maps take seconds, so take only few samples
+    c.bench("map_Inland_huge", Benchmark::new("map_Inland_huge", |b| {
+        let mut map = HexMap::new(1000, 1000);
+        let gen = Inland::default();
+        b.iter(|| gen.generate(&mut map))
+    }).sample_size(10));
 }
 
 criterion_group!(benches, criterion_benchmark);
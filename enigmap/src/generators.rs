@@ -1,15 +1,24 @@
 use crate::hexmap::HexMap;
 use crate::hex::HexType;
+use crate::selection::{Selection, blend_border};
+use crate::utils::map_field;
+
+use std::error::Error;
+use std::fmt;
 
 mod circle;
 mod islands;
 mod inland;
 mod debug;
+mod heightmap;
+mod wfc;
 
 pub use self::circle::Circle;
 pub use self::islands::Islands;
 pub use self::inland::Inland;
 pub use self::debug::Debug;
+pub use self::heightmap::Heightmap;
+pub use self::wfc::Wfc;
 
 /// Trait for map generators
 /// 
@@ -18,6 +27,16 @@ pub trait MapGen {
     /// Main generation fuction
     fn generate(&self, hex_map: &mut HexMap);
 
+    /// Generates map and reports progress after every pass
+    ///
+    /// `progress` gets ratio of finished work in range `<0; 1>` and name of the finished pass.
+    /// When it returns `false`, remaining passes are skipped, `Cancelled` is returned and the map is left partially generated.
+    /// Generators without separate passes report only once, after the whole map is generated.
+    fn generate_with_progress(&self, hex_map: &mut HexMap, progress: &mut dyn FnMut(f32, &str) -> bool) -> Result<(), Cancelled> {
+        self.generate(hex_map);
+        report(progress, 1.0, "generate")
+    }
+
     /// Sets seed for noise and rng generators used while generating the map
     fn set_seed(&mut self, seed: u32);
 
@@ -37,19 +56,86 @@ pub trait MapGen {
 
     /// Changes type of hexes with neighbours with different type than itself
     fn clear_pass(&self, hex_map: &mut HexMap, from: HexType, to: HexType, strength: u32) {
-        let old_map = hex_map.clone();
-        for hex in &mut hex_map.field {
+        let changed = map_field(&hex_map.field, |_, hex| {
+            if hex.terrain_type != from {
+                return false;
+            }
             let mut diff_neighbours = 0;
             // check for neighbours
-            for (neighbour_x, neighbour_y) in hex.get_neighbours(&old_map) {
-                let index = old_map.coords_to_index(neighbour_x, neighbour_y).unwrap();
-                if hex.terrain_type != old_map.field[index].terrain_type {
+            for (neighbour_x, neighbour_y) in hex.get_neighbours(hex_map) {
+                let index = hex_map.coords_to_index(neighbour_x, neighbour_y).unwrap();
+                if hex.terrain_type != hex_map.field[index].terrain_type {
                     diff_neighbours += 1;
                 }
             }
-            if diff_neighbours > strength && from == hex.terrain_type {
+            diff_neighbours > strength
+        });
+        for (hex, changed) in hex_map.field.iter_mut().zip(changed) {
+            if changed {
                 hex.terrain_type = to;
             }
         }
     }
+
+    /// Generates map only in selected area, hexes outside of the selection are not changed
+    ///
+    /// Whole map is generated so the noise stays continuous, then selected hexes are copied.
+    /// Hexes in the selection closer than `blend_width` to its border are smoothed by `blend_border`.
+    /// # Panics
+    /// when selection was created for map of different size
+    fn generate_area(&self, hex_map: &mut HexMap, selection: &Selection, blend_width: u32) {
+        if !selection.matches(hex_map) {
+            panic!("selection has different size than the map");
+        }
+        let mut generated = hex_map.clone();
+        self.generate(&mut generated);
+        for (index, hex) in hex_map.field.iter_mut().enumerate() {
+            if selection.contains_index(index) {
+                *hex = generated.field[index];
+            }
+        }
+        blend_border(hex_map, selection, blend_width);
+    }
+}
+
+/// Error returned when generation was cancelled by progress callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "map generation was cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Calls progress callback, returns `Cancelled` when it asks to stop
+fn report(progress: &mut dyn FnMut(f32, &str) -> bool, fraction: f32, pass: &str) -> Result<(), Cancelled> {
+    if progress(fraction, pass) {
+        Ok(())
+    } else {
+        Err(Cancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_generation() {
+        let mut hexmap = HexMap::new(40, 30);
+        let mut passes = Vec::new();
+        let result = Islands::default().generate_with_progress(&mut hexmap, &mut |fraction, pass| {
+            passes.push((fraction, pass.to_string()));
+            pass != "mountain"
+        });
+        assert_eq!(Err(Cancelled), result);
+        assert_eq!(vec![(0.4, "land".to_string()), (0.55, "mountain".to_string())], passes);
+
+        let mut calls = 0;
+        assert!(Circle::default().generate_with_progress(&mut hexmap, &mut |_, _| { calls += 1; true }).is_ok());
+        assert_eq!(5, calls);
+    }
 }
@@ -0,0 +1,268 @@
+use png::{ColorType, Decoder, DecodingError, Transformations};
+
+use std::fs::File;
+use std::path::Path;
+
+use crate::hexmap::HexMap;
+use crate::hex::HexType;
+use crate::generators::MapGen;
+
+/// Generator that samples terrain from a grayscale heightmap image
+///
+/// Optionally uses second RGB image for choosing biomes of land tiles.
+/// Biome colors are matched to the closest color in `biome_colors`.
+/// ## Usage
+/// ```no_run
+/// use enigmap::{prelude::*, generators::Heightmap};
+///
+/// let mut hexmap = HexMap::new(100, 75);
+/// let mut gen = Heightmap::from_file("./heights.png").unwrap();
+/// gen.set_biome_file("./biomes.png").unwrap();
+/// gen.generate(&mut hexmap);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    /// Height under which hexes are water, in range `<0; 1>`
+    pub sea_level: f32,
+    /// Depth under `sea_level` from which water becomes ocean
+    pub shallow_depth: f32,
+    /// Height from which hexes are mountains
+    pub mountain_level: f32,
+    /// Height from which hexes are impassable
+    pub impassable_level: f32,
+    /// Use bilinear interpolation when sampling heights
+    pub bilinear: bool,
+    /// Colors used when matching biome image to `HexType`
+    pub biome_colors: Vec<(HexType, [u8; 3])>,
+    heights: Layer<f32>,
+    biomes: Option<Layer<[u8; 3]>>,
+}
+
+impl Heightmap {
+    /// Creates new generator from grayscale 8-bit image data, one byte per pixel
+    /// # Panics
+    /// when buffer length is different from `width * height` or any of the dimensions is 0
+    pub fn from_buffer(width: u32, height: u32, data: &[u8]) -> Heightmap {
+        if width == 0 || height == 0 {
+            panic!("One of image dimensions is 0");
+        }
+        if data.len() != (width * height) as usize {
+            panic!("image buffer has incorrect size, got: {}, expected: {}", data.len(), width * height);
+        }
+        let heights = data.iter().map(|&val| f32::from(val) / 255.0).collect();
+
+        Heightmap{
+            sea_level: 0.4,
+            shallow_depth: 0.1,
+            mountain_level: 0.75,
+            impassable_level: 0.9,
+            bilinear: true,
+            biome_colors: DEFAULT_BIOME_COLORS.to_vec(),
+            heights: Layer{width, height, data: heights},
+            biomes: None
+        }
+    }
+
+    /// Creates new generator from png image
+    ///
+    /// Color images are converted to grayscale, alpha channel is ignored
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Heightmap, DecodingError> {
+        let (width, height, pixels) = Self::decode_png(path)?;
+        let data = pixels.iter().map(|pixel| {
+            (f32::from(pixel[0]) * 0.299 + f32::from(pixel[1]) * 0.587 + f32::from(pixel[2]) * 0.114).round() as u8
+        }).collect::<Vec<u8>>();
+
+        Ok(Heightmap::from_buffer(width, height, &data))
+    }
+
+    /// Sets biome image from RGB 8-bit image data, three bytes per pixel
+    ///
+    /// Biome image does not need to have the same size as the heightmap
+    /// # Panics
+    /// when buffer length is different from `width * height * 3` or any of the dimensions is 0
+    pub fn set_biome_buffer(&mut self, width: u32, height: u32, data: &[u8]) {
+        if width == 0 || height == 0 {
+            panic!("One of image dimensions is 0");
+        }
+        if data.len() != (width * height * 3) as usize {
+            panic!("image buffer has incorrect size, got: {}, expected: {}", data.len(), width * height * 3);
+        }
+        let biomes = data.chunks_exact(3).map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+        self.biomes = Some(Layer{width, height, data: biomes});
+    }
+
+    /// Sets biome image from png image
+    pub fn set_biome_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), DecodingError> {
+        let (width, height, pixels) = Self::decode_png(path)?;
+        self.biomes = Some(Layer{width, height, data: pixels});
+        Ok(())
+    }
+
+    /// Removes biome image, land will be generated as `HexType::Field`
+    pub fn clear_biomes(&mut self) {
+        self.biomes = None;
+    }
+
+    /// Decodes png image into RGB pixels
+    ///
+    /// 16-bit images are reduced to 8 bits, palettes and low bit depths are expanded
+    fn decode_png<P: AsRef<Path>>(path: P) -> Result<(u32, u32, Vec<[u8; 3]>), DecodingError> {
+        let file = File::open(path)?;
+        let mut decoder = Decoder::new(file);
+        decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+        let (info, mut reader) = decoder.read_info()?;
+        let mut buf = vec![0; info.buffer_size()];
+        reader.next_frame(&mut buf)?;
+
+        let pixels = match info.color_type {
+            ColorType::Grayscale => buf.iter().map(|&val| [val, val, val]).collect(),
+            ColorType::GrayscaleAlpha => buf.chunks_exact(2).map(|pixel| [pixel[0], pixel[0], pixel[0]]).collect(),
+            ColorType::RGB => buf.chunks_exact(3).map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect(),
+            ColorType::RGBA => buf.chunks_exact(4).map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect(),
+            ColorType::Indexed => return Err(DecodingError::Other("indexed images are not supported".into()))
+        };
+        Ok((info.width, info.height, pixels))
+    }
+
+    /// Returns `HexType` with color closest to specified color
+    fn match_biome(&self, color: [u8; 3]) -> HexType {
+        let mut smallest_dist = u32::MAX;
+        let mut best_match = HexType::Field;
+        for (hex_type, biome_color) in &self.biome_colors {
+            let dist = color.iter().zip(biome_color.iter()).map(|(&a, &b)| {
+                (i32::from(a) - i32::from(b)).pow(2) as u32
+            }).sum();
+            if dist < smallest_dist {
+                smallest_dist = dist;
+                best_match = *hex_type;
+            }
+        }
+        best_match
+    }
+}
+
+impl MapGen for Heightmap {
+    fn generate(&self, hex_map: &mut HexMap) {
+        let ocean_level = self.sea_level - self.shallow_depth;
+
+        for hex in &mut hex_map.field {
+            // get relative position on the map
+            let (hex_center_x, hex_center_y) = hex.center();
+            let rel_x = hex_center_x / hex_map.absolute_size_x;
+            let rel_y = hex_center_y / hex_map.absolute_size_y;
+
+            let height = if self.bilinear {
+                self.heights.sample_bilinear(rel_x, rel_y)
+            } else {
+                *self.heights.sample(rel_x, rel_y)
+            };
+
+            hex.terrain_type = if height < ocean_level {
+                HexType::Ocean
+            } else if height < self.sea_level {
+                HexType::Water
+            } else if height >= self.impassable_level {
+                HexType::Impassable
+            } else if height >= self.mountain_level {
+                HexType::Mountain
+            } else {
+                match &self.biomes {
+                    Some(biomes) => self.match_biome(*biomes.sample(rel_x, rel_y)),
+                    None => HexType::Field
+                }
+            };
+        }
+    }
+
+    fn set_seed(&mut self, _seed: u32) {}
+    fn reset_seed(&mut self) {}
+}
+
+/// Image data sampled by relative coordinates
+#[derive(Debug, Clone)]
+struct Layer<T> {
+    width: u32,
+    height: u32,
+    data: Vec<T>
+}
+
+impl<T> Layer<T> {
+    /// Returns nearest pixel to relative coordinates in range `<0; 1>`
+    fn sample(&self, x: f32, y: f32) -> &T {
+        let pixel_x = (x * self.width as f32).max(0.0).min(self.width as f32 - 1.0) as usize;
+        let pixel_y = (y * self.height as f32).max(0.0).min(self.height as f32 - 1.0) as usize;
+        &self.data[pixel_x + pixel_y * self.width as usize]
+    }
+}
+
+impl Layer<f32> {
+    /// Returns value interpolated from four nearest pixels
+    fn sample_bilinear(&self, x: f32, y: f32) -> f32 {
+        // pixel centers are at half coordinates
+        let pixel_x = (x * self.width as f32 - 0.5).max(0.0).min(self.width as f32 - 1.0);
+        let pixel_y = (y * self.height as f32 - 0.5).max(0.0).min(self.height as f32 - 1.0);
+
+        let left = pixel_x.floor() as usize;
+        let top = pixel_y.floor() as usize;
+        let right = (left + 1).min(self.width as usize - 1);
+        let bottom = (top + 1).min(self.height as usize - 1);
+        let fract_x = pixel_x - left as f32;
+        let fract_y = pixel_y - top as f32;
+
+        let get = |x: usize, y: usize| self.data[x + y * self.width as usize];
+
+        let upper = get(left, top) * (1.0 - fract_x) + get(right, top) * fract_x;
+        let lower = get(left, bottom) * (1.0 - fract_x) + get(right, bottom) * fract_x;
+        upper * (1.0 - fract_y) + lower * fract_y
+    }
+}
+
+// same colors as default colors in renderers
+const DEFAULT_BIOME_COLORS: [(HexType, [u8; 3]); 12] = [
+    (HexType::Water, [74, 128, 214]),
+    (HexType::Field, [116, 191, 84]),
+    (HexType::Ice, [202, 208, 209]),
+    (HexType::Mountain, [77, 81, 81]),
+    (HexType::Forest, [86, 161, 54]),
+    (HexType::Ocean, [54, 108, 194]),
+    (HexType::Tundra, [62, 81, 77]),
+    (HexType::Desert, [214, 200, 109]),
+    (HexType::Jungle, [64, 163, 16]),
+    (HexType::Impassable, [140, 111, 83]),
+    (HexType::Swamp, [43, 66, 35]),
+    (HexType::Grassland, [186, 207, 97]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_heights() {
+        let mut gen = Heightmap::from_buffer(2, 1, &[0, 128]);
+        gen.bilinear = false;
+        let mut hexmap = HexMap::new(10, 4);
+        gen.generate(&mut hexmap);
+        assert_eq!(HexType::Ocean, hexmap.field[0].terrain_type);
+        assert_eq!(HexType::Field, hexmap.field[9].terrain_type);
+
+        gen.set_biome_buffer(1, 1, &[210, 200, 110]);
+        gen.generate(&mut hexmap);
+        assert_eq!(HexType::Desert, hexmap.field[9].terrain_type);
+    }
+
+    #[test]
+    fn sixteen_bit_file() {
+        let path = std::env::temp_dir().join("enigmap_heightmap_16.png");
+        {
+            let file = File::create(&path).unwrap();
+            let mut encoder = png::Encoder::new(file, 2, 1);
+            encoder.set_color(ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Sixteen);
+            encoder.write_header().unwrap().write_image_data(&[0x00, 0x10, 0x80, 0x40]).unwrap();
+        }
+        let gen = Heightmap::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Heightmap::from_buffer(2, 1, &[0x00, 0x80]).heights.data, gen.heights.data);
+    }
+}
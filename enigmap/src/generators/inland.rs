@@ -1,6 +1,7 @@
-use crate::generators::MapGen;
+use crate::generators::{MapGen, Cancelled, report};
 use crate::hexmap::HexMap;
 use crate::hex::{Hex, HexType};
+use crate::passes::{Pass, Lakes, WeightTree};
 
 use rand::prelude::*;
 
@@ -30,77 +31,56 @@ impl Inland {
         (area / area_size).max(1)
     }
 
-    fn fade_edge_probability(probabilities: &mut [f32], total_probability: &mut f32, hex_map: &HexMap, line_probabs: &mut [f32]) {
+    /// Makes centers less probable near edges which don't wrap
+    fn fade_edge_probability(&self, probabilities: &mut [f32], hex_map: &HexMap) {
         let fadeout = (hex_map.size_y as f32 * 0.1) as u32;
         for i in 0..fadeout {
             let fade_strength = (i as f32 / fadeout as f32).sqrt();
-            line_probabs[i as usize] = fade_strength * hex_map.size_x as f32;
-            line_probabs[hex_map.size_y as usize - i as usize - 1] = fade_strength * hex_map.size_x as f32;
             for x in 0..hex_map.size_x {
                 // top
-                let index = (x + i * hex_map.size_x) as usize;
-                let temp = probabilities[index];
-                probabilities[index] *= fade_strength;
-                *total_probability -= temp - probabilities[index];
-
+                probabilities[(x + i * hex_map.size_x) as usize] *= fade_strength;
                 // bottom
-                let index = (x + (hex_map.size_y - 1 - i) * hex_map.size_x) as usize;
-                let temp = probabilities[index];
-                probabilities[index] *= fade_strength;
-                *total_probability -= temp - probabilities[index];
+                probabilities[(x + (hex_map.size_y - 1 - i) * hex_map.size_x) as usize] *= fade_strength;
             }
         }
-    }
-
-    fn fade_probability_ring(total_probab: &mut f32, probabs: &mut [f32], line_probabs: &mut [f32], ring: &[(i32, i32)], strength: f32, offset: (i32, i32), hex_map: &HexMap) {
-        if strength > 0.995 {
+        if self.wrap_map {
             return;
         }
-        for (hex_x, hex_y) in ring {
-            let coords = Hex::unwrap_coords(hex_x + offset.0, hex_y + offset.1, hex_map.size_x);
-            let index = hex_map.coords_to_index(coords.0, coords.1);
-            let index = match index {
-                Some(val) => val,
-                None => continue
-            };
-            let old_prob = probabs[index];
-            probabs[index] *= strength;
-            *total_probab -= old_prob - probabs[index];
-            line_probabs[coords.1 as usize] -= old_prob - probabs[index];
+        let fadeout = (hex_map.size_x as f32 * 0.1) as u32;
+        for i in 0..fadeout {
+            let fade_strength = (i as f32 / fadeout as f32).sqrt();
+            for y in 0..hex_map.size_y {
+                // left
+                probabilities[(i + y * hex_map.size_x) as usize] *= fade_strength;
+                // right
+                probabilities[(hex_map.size_x - 1 - i + y * hex_map.size_x) as usize] *= fade_strength;
+            }
         }
     }
 
-    fn random_hex_index(rng: &mut StdRng, total_probability: f32, probabilities: &[f32], line_probabs: &[f32], width: usize) -> (usize, usize) {
-        let random_number = rng.gen::<f32>() * total_probability;
-        let mut total = 0.0;
-        let mut line_guess = 0;
-        let mut hex = 0;
-        for i in 0..line_probabs.len() {
-            if total <= random_number {
-                total += line_probabs[i];
-            } else {
-                total -= line_probabs[i - 1];
-                line_guess = i - 1;
-                hex = line_guess * width;
-                break;
+    fn fade_probability_ring(&self, probabilities: &mut WeightTree, ring: &[(i32, i32)], strength: f32, offset: (i32, i32), hex_map: &HexMap) {
+        for (hex_x, hex_y) in ring {
+            let (x, y) = (hex_x + offset.0, hex_y + offset.1);
+            if y < 0 || y >= hex_map.size_y as i32 {
+                continue;
             }
-        }
-        for k in hex..probabilities.len() {
-            if total < random_number {
-                total += probabilities[k];
-            } else {
-                return (k, line_guess);
+            let coords = Hex::unwrap_coords(x, y, hex_map.size_x);
+            if !self.wrap_map && coords != (x, y) {
+                continue;
             }
+            let index = match hex_map.coords_to_index(coords.0, coords.1) {
+                Some(val) => val,
+                None => continue
+            };
+            let old_prob = probabilities.get(index);
+            probabilities.set(index, old_prob * strength);
         }
-        (probabilities.len() - 1, line_probabs.len() - 1)
     }
 
     fn generate_centers(&self, hex_map: &HexMap, rng: &mut StdRng) -> Vec<usize> {
         let region_count = self.get_region_count(hex_map);
 
         let mut probabilities = vec![1.0; hex_map.get_area() as usize];
-        let mut line_probabilities = vec![hex_map.size_x as f32; hex_map.size_y as usize];
-        let mut total_probability = hex_map.get_area() as f32;
 
         let distance = (hex_map.get_avg_size() as f32 * 0.2) as u32;
         let strength = 1.1;
@@ -109,96 +89,88 @@ impl Inland {
             ((dist - 2.0).max(0.0) / strength).log10().min(1.0).max(0.0)
         };
 
-        // make centers less probable on top and bottom
-        Self::fade_edge_probability(&mut probabilities, &mut total_probability, hex_map, &mut line_probabilities);
+        // make centers less probable on edges
+        self.fade_edge_probability(&mut probabilities, hex_map);
+        let mut probabilities = WeightTree::new(probabilities);
 
-        // cache rings for reuse
-        let mut rings = vec![vec![];(distance - 1) as usize];
+        // cache rings for reuse, rings which don't change probability are skipped
         let hex = Hex::from_coords(0, 0);
-
-        for r in 1..distance {
-            let ring = hex.get_ring(hex_map, r);
-            rings[r as usize - 1] = ring;
-        }
+        let rings = (1..distance)
+            .map(|r| (get_mult(r as f32), r))
+            .take_while(|&(mult, _)| mult <= 0.995)
+            .map(|(mult, r)| (mult, hex.get_ring(hex_map, r)))
+            .collect::<Vec<_>>();
 
         let mut centers = Vec::with_capacity(region_count as usize);
 
         for _i in 0..region_count {
-            let (hex, line) = Self::random_hex_index(rng, total_probability, &probabilities, &line_probabilities, hex_map.size_x as usize);
+            let total = probabilities.total();
+            let hex = match probabilities.find(rng.gen::<f32>() * total) {
+                Some(hex) => hex,
+                None => break
+            };
             centers.push(hex);
-            total_probability -= probabilities[hex];
-            line_probabilities[line] -= probabilities[hex];
-            probabilities[hex] = 0.0;
+            probabilities.set(hex, 0.0);
             let offset = HexMap::index_to_coords(hex_map, hex as u32);
             // now update probabilities
-            for r in 1..distance {
-                let mult = get_mult(r as f32);
-                Self::fade_probability_ring(&mut total_probability, &mut probabilities, &mut line_probabilities, &rings[r as usize - 1], mult, offset, hex_map);
+            for (mult, ring) in &rings {
+                self.fade_probability_ring(&mut probabilities, ring, *mult, offset, hex_map);
             }
         }
 
         centers
     }
 
-    fn generate_regions(&self, rng: &mut StdRng, hex_map: &mut HexMap, centers: &Vec<usize>) -> Regions {
-        let mut regions = Regions::new(centers.len());
-        let mut frontiers: Vec<Vec<usize>> = Vec::new();
-
-        for (i, &reg) in centers.iter().enumerate() {
-            regions.regions[i].center = reg;
-            regions.regions[i].hexes.push(reg);
-            let neighbours = hex_map.field[reg].get_neighbours(&hex_map);
-
-            let mut frontier = Vec::new();
-
-            for (x,y) in neighbours {
-                let index = hex_map.coords_to_index(x,y).unwrap();
-                frontier.push(index);
-            }
-            frontiers.push(frontier);
+    fn neighbours(&self, hex_map: &HexMap, index: usize) -> Vec<(i32, i32)> {
+        if self.wrap_map {
+            hex_map.field[index].get_neighbours(hex_map)
+        } else {
+            hex_map.field[index].get_neighbours_bounded(hex_map)
         }
+    }
 
-        let mut hexes_to_set = hex_map.get_area() - centers.len() as u32;
-        let mut hexes_set = vec![None; hex_map.get_area() as usize];
-        for (index, center) in centers.iter().enumerate() {
-            hexes_set[*center] = Some(index);
+    fn generate_regions(&self, rng: &mut StdRng, hex_map: &HexMap, centers: &[usize]) -> Regions {
+        let mut regions = Regions::new(centers.len());
+        // hexes are owned by region from the moment they enter its frontier, so every hex is visited once
+        let mut owners = vec![None; hex_map.get_area() as usize];
+        for (i, &center) in centers.iter().enumerate() {
+            owners[center] = Some(i);
         }
 
-        'filler: loop {
-            for i in 0..regions.regions.len() {
-                if hexes_to_set == 0 {
-                    break 'filler;
+        let mut frontiers: Vec<Vec<usize>> = vec![Vec::new(); centers.len()];
+        for (i, &center) in centers.iter().enumerate() {
+            regions.regions[i].center = center;
+            regions.regions[i].hexes.push(center);
+            for (x, y) in self.neighbours(hex_map, center) {
+                let index = hex_map.coords_to_index(x, y).unwrap();
+                if owners[index].is_none() {
+                    owners[index] = Some(i);
+                    frontiers[i].push(index);
                 }
+            }
+        }
 
+        let mut active = frontiers.iter().filter(|frontier| !frontier.is_empty()).count();
+        while active > 0 {
+            for i in 0..regions.regions.len() {
                 if frontiers[i].is_empty() {
                     continue;
                 }
                 let hex_index = rng.gen_range(0, frontiers[i].len());
-                let hex = frontiers[i][hex_index];
-
-                if let Some(val) = hexes_set[hex] {
-                    if val != i {
-                        frontiers[i].remove(hex_index);
-                        continue;
-                    }
-                }
-
-                frontiers[i].remove(hex_index);
-
+                let hex = frontiers[i].swap_remove(hex_index);
                 regions.regions[i].hexes.push(hex);
-                hexes_set[hex] = Some(i);
 
-                let neighbours = hex_map.field[hex].get_neighbours(&hex_map);
-
-                for (x,y) in neighbours {
-                    let index = hex_map.coords_to_index(x,y).unwrap();
-                    if hexes_set[index].is_none() && !frontiers[i].contains(&index) {
+                for (x, y) in self.neighbours(hex_map, hex) {
+                    let index = hex_map.coords_to_index(x, y).unwrap();
+                    if owners[index].is_none() {
+                        owners[index] = Some(i);
                         frontiers[i].push(index);
-                        hexes_set[index] = Some(i);
                     }
                 }
 
-                hexes_to_set -= 1;
+                if frontiers[i].is_empty() {
+                    active -= 1;
+                }
             }
         }
 
@@ -219,7 +191,6 @@ impl Inland {
         let base = Inland::search_type(reg.temperature, reg.flatness, reg.humidity);
 
         let mut mountains = Vec::with_capacity(reg.hexes.len());
-        let mut lakes = Vec::with_capacity(reg.hexes.len());
 
         // create base
         if reg.water_region {
@@ -231,20 +202,12 @@ impl Inland {
                 hex_map.field[*hex].terrain_type = base;
             }
 
-            let hum_fn = || {
-                reg.humidity.powi(3) * 0.4 - reg.humidity.powi(2) * 0.3 + reg.humidity * 0.08 + 0.01
-            };
-
             let mountain_fn = || {
                 reg.humidity.powi(3) * 0.3 - reg.humidity.powi(2) * 0.3 + reg.humidity * 0.2 + 0.04
             };
 
-            // create some lakes and mountains
+            // create some mountains, lakes are made by `Lakes` pass
             for hex in &reg.hexes {
-                if hum_fn() > rng.gen() {
-                    hex_map.field[*hex].terrain_type = HexType::Water;
-                    lakes.push(*hex);
-                }
                 if mountain_fn() > rng.gen() {
                     hex_map.field[*hex].terrain_type = HexType::Mountain;
                     mountains.push(*hex);
@@ -270,6 +233,11 @@ impl Inland {
 
 impl MapGen for Inland {
     fn generate(&self, hex_map: &mut HexMap) {
+        // callback never cancels
+        let _ = self.generate_with_progress(hex_map, &mut |_, _| true);
+    }
+
+    fn generate_with_progress(&self, hex_map: &mut HexMap, progress: &mut dyn FnMut(f32, &str) -> bool) -> Result<(), Cancelled> {
         let seed = match self.seed {
             Some(val) => val,
             None => random::<u32>()
@@ -278,8 +246,10 @@ impl MapGen for Inland {
         let mut rng = StdRng::from_seed(self.seed_to_rng_seed(seed));
 
         let centers = self.generate_centers(hex_map, &mut rng);
+        report(progress, 0.2, "centers")?;
 
         let mut regions = self.generate_regions(&mut rng, hex_map, &centers);
+        report(progress, 0.5, "regions")?;
 
         // create region parameters
         for region in &mut regions.regions {
@@ -300,6 +270,17 @@ impl MapGen for Inland {
         for reg in &regions.regions {
             self.decorate_reg(hex_map, reg, &mut rng);
         }
+        report(progress, 0.7, "decorator")?;
+
+        // more humid maps have more lakes
+        let lakes = Lakes{
+            min_depth: 0.12 - f32::from(self.humidity) * 0.1,
+            swamp_humidity: 1.0 - f32::from(self.humidity) * 0.5,
+            wrap_map: self.wrap_map,
+            ..Default::default()
+        };
+        lakes.apply(hex_map, seed);
+        report(progress, 1.0, "lakes")
     }
 
     fn set_seed(&mut self, seed: u32) {
@@ -398,3 +379,29 @@ const TYPE_COORDS: [(HexType, f32, f32, f32); 11] = [
     (HexType::Grassland, 0.45, 0.6, 0.5),
     (HexType::Grassland, 0.35, 0.55, 0.3),
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions_cover_map() {
+        let hexmap = HexMap::new(60, 40);
+        for &wrap_map in &[true, false] {
+            let mut gen = Inland::default();
+            gen.set_wrap_map(wrap_map);
+            let mut rng = StdRng::from_seed(gen.seed_to_rng_seed(4));
+            let centers = gen.generate_centers(&hexmap, &mut rng);
+            let regions = gen.generate_regions(&mut rng, &hexmap, &centers);
+
+            let mut covered = vec![0; hexmap.field.len()];
+            for region in &regions.regions {
+                assert_eq!(region.center, region.hexes[0]);
+                for &hex in &region.hexes {
+                    covered[hex] += 1;
+                }
+            }
+            assert!(covered.iter().all(|&count| count == 1));
+        }
+    }
+}
@@ -1,138 +1,180 @@
 use rand::prelude::*;
 use rand::rngs::StdRng;
-use noise::{Fbm, NoiseFn, Seedable, Worley, Perlin};
+use noise::{Fbm, NoiseFn, Seedable, Perlin};
 use std::f32;
+use std::cmp::Ordering;
 
 use crate::hexmap::HexMap;
 use crate::hex::HexType;
-use crate::generators::MapGen;
+use crate::generators::{MapGen, Cancelled, report};
+use crate::utils::map_field;
+use crate::passes::{Pass, Coast, Cryosphere, Mountains, distance_field};
+
+/// Maximal number of land generation attempts
+const MAX_LAND_ITERATIONS: u32 = 6;
+/// Accepted difference between target and generated land, relative to map area
+const LAND_TOLERANCE: f32 = 0.01;
 
 /// Generator that generates multiple islands
 #[derive(Debug, Clone, Copy)]
 pub struct Islands {
     seed: u32,
     using_seed: bool,
-    pub ocean_distance: u32
+    pub ocean_distance: u32,
+    /// Number of big landmasses
+    pub continent_count: u32,
+    /// Ratio of land to all hexes
+    pub land_fraction: f32,
+    /// Ratio of land in continents, rest are small islands
+    pub continent_share: f32,
+    /// How much continent sizes differ, in range `<0; 1>`
+    pub size_variance: f32,
+    /// Size of small islands, bigger values create fewer and bigger islands
+    pub island_size: f32,
+    /// Minimal number of ocean hexes between continents
+    pub min_separation: u32,
+    pub mountains: Mountains,
+    pub cryosphere: Cryosphere,
 }
 
 impl Islands {
-    /// Generates ice on top and bootom
-    fn ice_pass<T>(&self, hex_map: &mut HexMap, gen: &T, noise_scale: f64, seed: u32)
-        where T: NoiseFn<[f64; 2]>
+    /// Generates land, returns index of continent owning every hex
+    fn land_pass<T>(&self, hex_map: &mut HexMap, gen: &T, noise_scale: f64, seed: u32) -> Vec<usize>
+        where T: NoiseFn<[f64; 2]> + Sync
     {
-        // generate ice
-        for hex in &mut hex_map.field {
-            // hex specific fields
+        let noise = map_field(&hex_map.field, |_, hex| {
             let center = hex.center();
-            let worley_val = gen.get([center.0 as f64 * noise_scale + seed as f64, center.1 as f64 * noise_scale]);
-            let dst_to_edge = 1.0 - ((center.1 / hex_map.absolute_size_y - 0.5).abs() * 2.0);
-            
-            // make sure ice is certain to appear
-            if hex.y == 0 || hex.y == (hex_map.size_y as i32 - 1) {
-                hex.terrain_type = HexType::Ice;
-            }
-            // ice noise on top and bottom
-            let noisy_dst_to_edge = dst_to_edge + (worley_val * 0.03) as f32;
-            if noisy_dst_to_edge < 0.12 {
-                hex.terrain_type = HexType::Ice;
-            }
-        }
-        // clear up ice by removing some isalnds of ice and water
-        for _ in 0..2 {
-            self.clear_pass(hex_map, HexType::Ocean, HexType::Ice, 3);
-            self.clear_pass(hex_map, HexType::Ice, HexType::Ocean, 3);
-        }
-    }
+            gen.get([center.0 as f64 * noise_scale + seed as f64, center.1 as f64 * noise_scale]) as f32
+        });
+        let island_scale = noise_scale / f64::from(self.island_size.max(0.01));
+        let island_noise = map_field(&hex_map.field, |_, hex| {
+            let center = hex.center();
+            gen.get([center.0 as f64 * island_scale + seed as f64, center.1 as f64 * island_scale]) as f32
+        });
 
+        // create bigger landmasses
+        // choose random points at centers of those landmasses
+        let mut rng = StdRng::from_seed(self.seed_to_rng_seed(seed));
+        let continents = (0..self.continent_count).map(|_| Continent::random(hex_map, &mut rng, self.size_variance)).collect::<Vec<_>>();
 
-    /// Generates land
-    fn land_pass<T>(&self, hex_map: &mut HexMap, gen: &T, noise_scale: f64, seed: u32)
-        where T:NoiseFn<[f64; 2]>
-    {
-        // generate and clear up small islands
-        for hex in &mut hex_map.field {
-            if let HexType::Ocean = hex.terrain_type {
+        // every hex belongs to continent with the highest score
+        let mut owners = vec![0; hex_map.field.len()];
+        let mut scores = vec![f32::MIN; hex_map.field.len()];
+        if !continents.is_empty() {
+            for (index, hex) in hex_map.field.iter().enumerate() {
                 let center = hex.center();
-                let noise_val = gen.get([center.0 as f64 * noise_scale + seed as f64, center.1 as f64 * noise_scale]);
-                if noise_val > 0.36 {
-                    hex.terrain_type = HexType::Field;
+                for (owner, continent) in continents.iter().enumerate() {
+                    let score = continent.score(center, hex_map.absolute_size_x) - noise[index] * 3.0;
+                    if score > scores[index] {
+                        scores[index] = score;
+                        owners[index] = owner;
+                    }
                 }
             }
         }
-        for _ in 0..3 {
-            self.clear_pass(hex_map, HexType::Field, HexType::Ocean, 3);
-            self.clear_pass(hex_map, HexType::Ocean, HexType::Field, 3);
+
+        // keep continents apart by leaving ocean around borders between them
+        let borders = distance_field(hex_map, true, |hex| {
+            let index = hex_map.coords_to_index(hex.x, hex.y).unwrap();
+            hex.get_neighbours(hex_map).iter().any(|&(x, y)| owners[hex_map.coords_to_index(x, y).unwrap()] != owners[index])
+        });
+        let gap = (self.min_separation + 1) / 2;
+        let blocked = borders.iter().map(|&distance| distance < gap).collect::<Vec<bool>>();
+        for (score, &blocked) in scores.iter_mut().zip(&blocked) {
+            if blocked {
+                *score = f32::MIN;
+            }
         }
 
-        // create bigger landmasses
-        // choose random points at centers of those landmasses
-        let mut rng = StdRng::from_seed(self.seed_to_rng_seed(seed));
-        for _ in 0..3 {
-            // get first focus
-            let x: f32 = rng.gen_range(0.0, hex_map.absolute_size_x);
-            let y: f32 = rng.gen_range(0.1, 0.9) * hex_map.absolute_size_y;
-            let first_focus = (x,y);
-
-            // get aproximate center of the map
-            let center = (hex_map.absolute_size_x / 2.0 + rng.gen_range(-10.0, 10.0), hex_map.absolute_size_y / 2.0 + rng.gen_range(-10.0, 10.0));
-
-            // get unit vector with direction from first focus to center
-            let mut vector = (center.0 - first_focus.0, center.1 - first_focus.1);
-            let len = (vector.0.powi(2) + vector.1.powi(2)).sqrt();
-            vector.0 /= len;
-            vector.1 /= len;
-
-            // multiply it by random value and get second focus
-            let island_len: f32 = rng.gen_range(hex_map.absolute_size_y / 4.0, hex_map.absolute_size_y / 2.5);
-            let second_focus = (first_focus.0 + vector.0 * island_len, first_focus.1 + vector.1 * island_len);
-
-            // between them is center of the big island
-            let center_focus = ((first_focus.0 + second_focus.0) / 2.0, (first_focus.1 + second_focus.1) / 2.0);
-
-            // now generate landmasses
-            for hex in &mut hex_map.field {
-                // skip tiles that aren't water
-                match hex.terrain_type {
-                    HexType::Ocean => {},
-                    _ => continue
-                };
-                let center = hex.center();
-                let noise_val = gen.get([center.0 as f64 * noise_scale + seed as f64, center.1 as f64 * noise_scale]);
-                // get distances to selecte points and generate islands from those
-                let first_dst = ((center.0 - first_focus.0).powi(2) + (center.1 - first_focus.1).powi(2)).sqrt();
-                let second_dst = ((center.0 - second_focus.0).powi(2) + (center.1 - second_focus.1).powi(2)).sqrt();
-                let center_dst = ((center.0 - center_focus.0).powi(2) + (center.1 - center_focus.1).powi(2)).sqrt() * 0.6;
-                let elipse_dst = f32::min(center_dst, f32::min(first_dst, second_dst)) / hex_map.absolute_size_x * 100.0;
-                if (noise_val as f32 * 3.0 + elipse_dst) < 4.0 {
-                    hex.terrain_type = HexType::Field;
+        let area = hex_map.field.len() as f32;
+        let land_target = self.land_fraction.max(0.0).min(1.0) * area;
+        let continent_share = if continents.is_empty() { 0.0 } else { self.continent_share.max(0.0).min(1.0) };
+        let mut continent_target = land_target * continent_share;
+        let mut island_target = land_target - continent_target;
+
+        // cleanup changes amount of land, so adjust thresholds until the result is close enough
+        let mut continent_threshold = f32::MAX;
+        for _ in 0..MAX_LAND_ITERATIONS {
+            continent_threshold = Self::threshold(&scores, continent_target);
+            let island_noise = island_noise.iter().zip(&scores).zip(&blocked).map(|((&noise, &score), &blocked)| {
+                if blocked || score > continent_threshold { f32::MIN } else { noise }
+            }).collect::<Vec<f32>>();
+            let island_threshold = Self::threshold(&island_noise, island_target);
+
+            for (index, hex) in hex_map.field.iter_mut().enumerate() {
+                if hex.terrain_type == HexType::Ocean || hex.terrain_type == HexType::Field {
+                    hex.terrain_type = if scores[index] > continent_threshold || island_noise[index] > island_threshold {
+                        HexType::Field
+                    } else {
+                        HexType::Ocean
+                    };
+                }
+            }
+            for _ in 0..3 {
+                self.clear_pass(hex_map, HexType::Field, HexType::Ocean, 3);
+                self.clear_pass(hex_map, HexType::Ocean, HexType::Field, 3);
+            }
+            // cleanup can fill the gaps between continents
+            for (hex, &blocked) in hex_map.field.iter_mut().zip(&blocked) {
+                if blocked && hex.terrain_type == HexType::Field {
+                    hex.terrain_type = HexType::Ocean;
                 }
             }
+
+            let land = hex_map.field.iter().filter(|hex| hex.terrain_type == HexType::Field).count() as f32;
+            debug_println!("Land fraction: {}", land / area);
+            if (land - land_target).abs() <= area * LAND_TOLERANCE {
+                break;
+            }
+            let correction = land_target / land.max(1.0);
+            continent_target = (continent_target * correction).min(area);
+            island_target = (island_target * correction).min(area);
+        }
+
+        // continents can disappear when their land is too close to the others
+        let mut has_land = vec![false; continents.len()];
+        for ((hex, &owner), &score) in hex_map.field.iter().zip(&owners).zip(&scores) {
+            if hex.terrain_type == HexType::Field && score > continent_threshold {
+                has_land[owner] = true;
+            }
+        }
+        let count = has_land.iter().filter(|&&land| land).count();
+        if count < continents.len() {
+            debug_println!("Only {} of {} continents generated", count, continents.len());
+        }
+        owners
+    }
+
+    /// Returns value above which is `target` values
+    fn threshold(values: &[f32], target: f32) -> f32 {
+        let mut sorted = values.iter().copied().filter(|&value| value > f32::MIN).collect::<Vec<f32>>();
+        let count = (target.round() as usize).min(sorted.len());
+        if count == 0 {
+            return f32::MAX;
         }
+        sorted.sort_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        if count == sorted.len() {
+            return f32::MIN;
+        }
+        (sorted[count - 1] + sorted[count]) / 2.0
     }
 
     /// Changes tiles with `HexType::FIELD` to something different based on position
     fn decorator_pass<T>(&self, hex_map: &mut HexMap, gen: &T, noise_scale: f64, seed: u32)
-        where T:NoiseFn<[f64; 2]>
+        where T: NoiseFn<[f64; 2]> + Sync
     {
-
-        let mut rng = StdRng::from_seed(self.seed_to_rng_seed(seed));
-        for hex in &mut hex_map.field {
-            // skip everything thats not land and generate mountains
+        let climate = map_field(&hex_map.field, |_, hex| {
+            // skip everything thats not land
             match hex.terrain_type {
-                HexType::Field => {
-                    if rng.gen::<f32>() < 0.04 {
-                        hex.terrain_type = HexType::Mountain;
-                        continue;
-                    }
-                }, 
-                _ => continue
+                HexType::Field => {},
+                _ => return hex.terrain_type
             };
 
             let center = hex.center();
             let dst_to_edge = 1.0 - ((center.1 / hex_map.absolute_size_y - 0.5).abs() * 2.0);
             let noise_val = gen.get([center.0 as f64 * noise_scale + seed as f64, center.1 as f64 * noise_scale]);
             let temperature = 70.0 * dst_to_edge - 20.0 + noise_val as f32 * 5.0;
-            hex.terrain_type = if temperature < -5.0 {
+            if temperature < -5.0 {
                 HexType::Tundra
             } else if temperature > -5.0 && temperature < 25.0 && noise_val > -0.6 {
                 HexType::Forest
@@ -140,43 +182,39 @@ impl Islands {
                 HexType::Desert
             } else {
                 HexType::Field
-            };
+            }
+        });
+        for (hex, terrain_type) in hex_map.field.iter_mut().zip(climate) {
+            hex.terrain_type = terrain_type;
         }
 
         // generate jungles by computing vector field for wind
         // areas which have wind pointed to ocean will be deserts
         // and areas with wind blowing to them will be jungles
-        let mut wind_field: Vec<(f32, f32)> = Vec::with_capacity(hex_map.field.len());
-        for hex in &hex_map.field {
+        let wind_field = map_field(&hex_map.field, |_, hex| {
             let center = hex.center();
             let noise_val_x = gen.get([center.0 as f64 * 0.15 * noise_scale + seed as f64, center.1 as f64 * 0.15 * noise_scale]) as f32;
             let noise_val_y = gen.get([center.0 as f64 * 0.15 * noise_scale - seed as f64, center.1 as f64 * 0.15 * noise_scale]) as f32;
             let len = (noise_val_x.powi(2) + noise_val_y.powi(2)).sqrt() as f32;
-            wind_field.push((noise_val_x / len, noise_val_y / len));
-        }
+            (noise_val_x / len, noise_val_y / len)
+        });
 
-        let old_map = hex_map.clone();
-        for (index, hex) in hex_map.field.iter_mut().enumerate() {
+        let jungles = map_field(&hex_map.field, |index, hex| {
             // skip not deserts
             match hex.terrain_type {
                 HexType::Desert => {},
-                _ => continue
+                _ => return false
             }
             let (x_wind, y_wind) = wind_field[index];
 
             let center = hex.center();
 
-            let target_x = center.0 + x_wind * old_map.get_avg_size() as f32 * 0.2;
-            let target_y = center.1 + y_wind * old_map.get_avg_size() as f32 * 0.2;
+            let target_x = center.0 + x_wind * hex_map.get_avg_size() as f32 * 0.2;
+            let target_y = center.1 + y_wind * hex_map.get_avg_size() as f32 * 0.2;
 
-            let target_hex_index = old_map.get_closest_hex_index(target_x, target_y);
+            let target_hex_index = hex_map.get_closest_hex_index(target_x, target_y);
 
-            match old_map.field[target_hex_index].terrain_type {
-                HexType::Water | HexType::Ocean => {
-                    hex.terrain_type = HexType::Jungle;
-                },
-                _ => {}
-            } 
+            matches!(hex_map.field[target_hex_index].terrain_type, HexType::Water | HexType::Ocean)
             /* debug wind direction
             if x_wind > 0.0 && y_wind > 0.0 {
                 hex.terrain_type = HexType::Water;
@@ -187,71 +225,24 @@ impl Islands {
             } else {
                 hex.terrain_type = HexType::Desert;
             }*/
-        }
-    }
-
-    /// Generates oceans by changing `HexType::Ocean` tiles into `HexType::Water`
-    /// 
-    /// Uses same generator as land pass for better ocean generation
-    fn ocean_pass<T>(&self, hex_map: &mut HexMap, gen: &T, noise_scale: f64, seed: u32)
-        where T:NoiseFn<[f64; 2]>
-    {
-        let mut land_tiles = 0;
-        // copy only land tiles into 2d array
-        let mut old_field: Vec<Vec<(i32, i32)>> = vec![Vec::new(); hex_map.size_y as usize];
-        for (line_num, line) in &mut hex_map.field.chunks_exact(hex_map.size_x as usize).enumerate() {
-            for hex in line {
-                match hex.terrain_type {
-                    HexType::Water | HexType::Ice | HexType::Ocean => continue,
-                    _ => {
-                        // copy only coordinates
-                        old_field[line_num].push((hex.x, hex.y));
-                        land_tiles+=1;
-                    }
-                };
+        });
+        for (hex, jungle) in hex_map.field.iter_mut().zip(jungles) {
+            if jungle {
+                hex.terrain_type = HexType::Jungle;
             }
         }
+    }
 
-        // don't even do ocean pass if there isn't land
-        if land_tiles == 0 {
-            return;
-        }
-
-        'hex: for hex in &mut hex_map.field {
-            // skip everything thats not ocean
-            match hex.terrain_type {
-                HexType::Ocean => {}, 
-                _ => continue
-            };
-            let mut dst_to_land = u32::max_value();
-            let hex_center = hex.center();
-            let noise_val = gen.get([hex_center.0 as f64 * noise_scale + seed as f64, hex_center.1 as f64 * noise_scale]);
-
-            // get upper and lower boundary on lines in which can land be found
-            let min_y = (hex.y - self.ocean_distance as i32).max(0) as usize;
-            let max_y = (hex.y + self.ocean_distance as i32).min(hex_map.size_y as i32 - 1) as usize;
-
-            // get distance to land
-            for line in &old_field[min_y..=max_y] {
-                let mut distance_in_line = u32::max_value();
-                for other in line {
-                    let dst = hex.distance_to( other.0, other.1);
-                    // if the second hex on line is further away, don't even compute the whole line
-                    if dst > distance_in_line {
-                        break;
-                    }
-                    distance_in_line = dst;
-                    if dst < dst_to_land {
-                        dst_to_land = dst;
-                        // spawn water and make sure we have at least one tile
-                        if dst_to_land <= self.ocean_distance && noise_val >= 0.14 || dst_to_land == 1 {
-                            hex.terrain_type = HexType::Water;
-                            continue 'hex;
-                        }
-                    }
-                }
-            }
-        }
+    /// Generates oceans by changing `HexType::Ocean` tiles into `HexType::Water` near land
+    fn ocean_pass(&self, hex_map: &mut HexMap, noise_scale: f64, seed: u32) {
+        let coast = Coast{
+            shelf_distance: self.ocean_distance,
+            shelf_type: HexType::Water,
+            jitter: self.ocean_distance as f32 * 0.5,
+            noise_scale,
+            ..Default::default()
+        };
+        coast.apply(hex_map, seed);
         //clear that up a little bit
         self.clear_pass(hex_map, HexType::Ocean, HexType::Water, 3);
     }
@@ -259,16 +250,32 @@ impl Islands {
 
 impl Default for Islands {
     fn default() -> Islands {
-        Islands{seed: 0, using_seed: false, ocean_distance: 5}
+        Islands{
+            seed: 0,
+            using_seed: false,
+            ocean_distance: 5,
+            continent_count: 3,
+            land_fraction: 0.2,
+            continent_share: 0.75,
+            size_variance: 0.3,
+            island_size: 1.0,
+            min_separation: 2,
+            mountains: Mountains::default(),
+            cryosphere: Cryosphere::default()
+        }
     }
 }
 
 impl MapGen for Islands {
     fn generate(&self, hex_map: &mut HexMap) {
+        // callback never cancels
+        let _ = self.generate_with_progress(hex_map, &mut |_, _| true);
+    }
+
+    fn generate_with_progress(&self, hex_map: &mut HexMap, progress: &mut dyn FnMut(f32, &str) -> bool) -> Result<(), Cancelled> {
         hex_map.fill(HexType::Ocean);
 
         // init generators
-        let w = Worley::new();
         let f = Fbm::new();
         let p = Perlin::new();
         let seed = if self.using_seed {
@@ -278,22 +285,27 @@ impl MapGen for Islands {
         };
 
         debug_println!("seed: {:?}", seed);
-        w.set_seed(seed);
         p.set_seed(seed);
-        w.enable_range(true);
 
         // noise scale
         let noise_scale = 60.0 / hex_map.absolute_size_x as f64;
         let land_noise_scale = 8.0 / hex_map.absolute_size_x as f64;
         
-        self.ice_pass(hex_map, &w, noise_scale, seed);
-        debug_println!("Ice generated");
         self.land_pass(hex_map, &f, land_noise_scale, seed);
         debug_println!("Land generated");
+        report(progress, 0.4, "land")?;
+        self.mountains.apply(hex_map, seed);
+        debug_println!("Mountains generated");
+        report(progress, 0.55, "mountain")?;
         self.decorator_pass(hex_map, &p, noise_scale, seed);
         debug_println!("Land features generated");
-        self.ocean_pass(hex_map, &f, land_noise_scale, seed);
+        report(progress, 0.75, "decorator")?;
+        self.cryosphere.apply(hex_map, seed);
+        debug_println!("Ice generated");
+        report(progress, 0.85, "ice")?;
+        self.ocean_pass(hex_map, land_noise_scale, seed);
         debug_println!("Oceans generated");
+        report(progress, 1.0, "ocean")
     }
 
     fn set_seed(&mut self, seed: u32) {
@@ -304,4 +316,88 @@ impl MapGen for Islands {
     fn reset_seed(&mut self) {
         self.using_seed = false;
     }
-}
\ No newline at end of file
+}
+
+/// Big elliptic landmass
+#[derive(Debug, Clone, Copy)]
+struct Continent {
+    first_focus: (f32, f32),
+    second_focus: (f32, f32),
+    center_focus: (f32, f32),
+    size: f32,
+}
+
+impl Continent {
+    fn random(hex_map: &HexMap, rng: &mut StdRng, size_variance: f32) -> Continent {
+        // get first focus
+        let x: f32 = rng.gen_range(0.0, hex_map.absolute_size_x);
+        let y: f32 = rng.gen_range(0.1, 0.9) * hex_map.absolute_size_y;
+        let first_focus = (x,y);
+
+        // get aproximate center of the map
+        let center = (hex_map.absolute_size_x / 2.0 + rng.gen_range(-10.0, 10.0), hex_map.absolute_size_y / 2.0 + rng.gen_range(-10.0, 10.0));
+
+        // get unit vector with direction from first focus to center
+        let mut vector = (center.0 - first_focus.0, center.1 - first_focus.1);
+        let len = (vector.0.powi(2) + vector.1.powi(2)).sqrt().max(f32::EPSILON);
+        vector.0 /= len;
+        vector.1 /= len;
+
+        // multiply it by random value and get second focus
+        let island_len: f32 = rng.gen_range(hex_map.absolute_size_y / 4.0, hex_map.absolute_size_y / 2.5);
+        let second_focus = (first_focus.0 + vector.0 * island_len, first_focus.1 + vector.1 * island_len);
+
+        // between them is center of the big island
+        let center_focus = ((first_focus.0 + second_focus.0) / 2.0, (first_focus.1 + second_focus.1) / 2.0);
+
+        let size = 1.0 + size_variance.max(0.0).min(1.0) * rng.gen_range(-0.9, 0.9);
+        Continent{first_focus, second_focus, center_focus, size}
+    }
+
+    /// Returns how much is the point inside of the continent, higher is closer
+    fn score(&self, point: (f32, f32), absolute_size_x: f32) -> f32 {
+        let distance = |focus: (f32, f32)| ((point.0 - focus.0).powi(2) + (point.1 - focus.1).powi(2)).sqrt();
+        let elipse_dst = f32::min(distance(self.center_focus) * 0.6, f32::min(distance(self.first_focus), distance(self.second_focus))) / absolute_size_x * 100.0;
+        -elipse_dst / self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn land_fraction() {
+        let mut hexmap = HexMap::new(100, 75);
+        hexmap.fill(HexType::Ocean);
+        let gen = Islands{land_fraction: 0.4, continent_count: 5, ..Default::default()};
+        let noise_scale = 8.0 / hexmap.absolute_size_x as f64;
+        let owners = gen.land_pass(&mut hexmap, &Fbm::new(), noise_scale, 9);
+
+        let land = hexmap.field.iter().filter(|hex| hex.terrain_type == HexType::Field).count() as f32 / hexmap.field.len() as f32;
+        assert!((land - 0.4).abs() < 0.03);
+
+        // every continent has some land
+        for owner in 0..5 {
+            assert!(hexmap.field.iter().zip(&owners).any(|(hex, &other)| other == owner && hex.terrain_type == HexType::Field));
+        }
+    }
+
+    #[test]
+    fn continent_separation() {
+        let mut hexmap = HexMap::new(60, 40);
+        hexmap.fill(HexType::Ocean);
+        let gen = Islands{land_fraction: 0.4, continent_count: 4, min_separation: 3, ..Default::default()};
+        let noise_scale = 8.0 / hexmap.absolute_size_x as f64;
+        let owners = gen.land_pass(&mut hexmap, &Fbm::new(), noise_scale, 4);
+
+        let land = hexmap.field.iter().zip(owners).filter(|(hex, _)| hex.terrain_type == HexType::Field).collect::<Vec<_>>();
+        for (index, (hex, owner)) in land.iter().enumerate() {
+            for (other, other_owner) in &land[index + 1..] {
+                if owner != other_owner {
+                    assert!(hex.distance_between(other) >= 3);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,460 @@
+use rand::prelude::*;
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::hexmap::HexMap;
+use crate::hex::HexType;
+use crate::generators::MapGen;
+
+/// Number of `HexType` variants usable as tiles, `HexType::Debug` is not used
+const TILE_COUNT: usize = 12;
+
+/// Wave function collapse generator
+///
+/// Each `Hex` starts with all tiles with non-zero weight possible.
+/// Hexes with the lowest entropy are collapsed one by one into single `HexType` and the adjacency rules are propagated to neighbours.
+/// When contradiction is found, the generator backtracks to the previous decision and tries different tile.
+/// ## Usage
+/// ```
+/// use enigmap::{prelude::*, HexType, generators::Wfc};
+///
+/// let mut hexmap = HexMap::new(40, 30);
+/// let mut gen = Wfc::default();
+/// // no deserts next to ice
+/// gen.forbid(HexType::Desert, HexType::Ice);
+/// gen.set_weight(HexType::Swamp, 0.0);
+/// // fixed hex which the rest of map must respect
+/// gen.add_constraint(20, 15, HexType::Mountain);
+/// gen.generate(&mut hexmap);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Wfc {
+    seed: Option<u32>,
+    wrap_map: bool,
+    weights: [f32; TILE_COUNT],
+    /// Bitmask of allowed neighbours for each tile
+    rules: [u16; TILE_COUNT],
+    constraints: Vec<(i32, i32, HexType)>,
+    /// Maximum number of backtracks before the remaining hexes are filled without respecting the rules
+    pub max_backtracks: u32,
+}
+
+impl Wfc {
+    /// Creates generator with all tiles allowed next to each other and with the same weight
+    pub fn new() -> Wfc {
+        Wfc{
+            seed: None,
+            wrap_map: true,
+            weights: [1.0; TILE_COUNT],
+            rules: [(1 << TILE_COUNT) - 1; TILE_COUNT],
+            constraints: Vec::new(),
+            max_backtracks: 10_000
+        }
+    }
+
+    /// Should the map repeat on the X axis
+    pub fn set_wrap_map(&mut self, value: bool) {
+        self.wrap_map = value;
+    }
+
+    /// Sets relative probability of `HexType`, zero weight disables the tile
+    /// # Panics
+    /// when `HexType::Debug` is used
+    pub fn set_weight(&mut self, hex_type: HexType, weight: f32) {
+        self.weights[Self::tile_index(hex_type)] = weight.max(0.0);
+    }
+
+    /// Forbids two types from being next to each other
+    /// # Panics
+    /// when `HexType::Debug` is used
+    pub fn forbid(&mut self, first: HexType, second: HexType) {
+        let first = Self::tile_index(first);
+        let second = Self::tile_index(second);
+        self.rules[first] &= !(1 << second);
+        self.rules[second] &= !(1 << first);
+    }
+
+    /// Allows two types to be next to each other
+    /// # Panics
+    /// when `HexType::Debug` is used
+    pub fn allow(&mut self, first: HexType, second: HexType) {
+        let first = Self::tile_index(first);
+        let second = Self::tile_index(second);
+        self.rules[first] |= 1 << second;
+        self.rules[second] |= 1 << first;
+    }
+
+    /// Returns `true` if types can be next to each other
+    pub fn is_allowed(&self, first: HexType, second: HexType) -> bool {
+        self.rules[Self::tile_index(first)] & (1 << Self::tile_index(second)) != 0
+    }
+
+    /// Sets `Hex` at specified coordinates to given type before the generation starts
+    /// # Panics
+    /// when `HexType::Debug` is used
+    pub fn add_constraint(&mut self, x: i32, y: i32, hex_type: HexType) {
+        Self::tile_index(hex_type);
+        self.constraints.push((x, y, hex_type));
+    }
+
+    /// Adds all hexes of given map which are not of `ignored` type as constraints
+    pub fn add_constraints_from_map(&mut self, hex_map: &HexMap, ignored: HexType) {
+        for hex in &hex_map.field {
+            match hex.terrain_type {
+                HexType::Debug(_, _, _) => continue,
+                hex_type if hex_type == ignored => continue,
+                hex_type => self.constraints.push((hex.x, hex.y, hex_type))
+            }
+        }
+    }
+
+    /// Removes all constraints
+    pub fn clear_constraints(&mut self) {
+        self.constraints.clear();
+    }
+
+    fn tile_index(hex_type: HexType) -> usize {
+        match hex_type {
+            HexType::Debug(_, _, _) => panic!("Debug type cannot be used as a tile"),
+            _ => i32::from(hex_type) as usize
+        }
+    }
+
+    fn entropy(&self, domain: u16) -> f32 {
+        let mut sum = 0.0;
+        let mut sum_log = 0.0;
+        for (tile, weight) in self.weights.iter().enumerate() {
+            if domain & (1 << tile) != 0 && *weight > 0.0 {
+                sum += weight;
+                sum_log += weight * weight.ln();
+            }
+        }
+        if sum == 0.0 {
+            return 0.0;
+        }
+        sum.ln() - sum_log / sum
+    }
+
+    /// Picks random tile from domain based on weights
+    fn pick_tile(&self, domain: u16, rng: &mut StdRng) -> usize {
+        let total: f32 = (0..TILE_COUNT).filter(|tile| domain & (1 << tile) != 0).map(|tile| self.weights[tile]).sum();
+        let mut random_number = rng.gen::<f32>() * total;
+        let mut last = 0;
+        for tile in 0..TILE_COUNT {
+            if domain & (1 << tile) == 0 {
+                continue;
+            }
+            last = tile;
+            random_number -= self.weights[tile];
+            if random_number < 0.0 {
+                return tile;
+            }
+        }
+        last
+    }
+
+    /// Picks tile with the highest weight from domain
+    fn best_tile(&self, domain: u16) -> usize {
+        let domain = if domain == 0 { (1 << TILE_COUNT) - 1 } else { domain };
+        let mut best = 0;
+        let mut best_weight = f32::MIN;
+        for tile in 0..TILE_COUNT {
+            if domain & (1 << tile) != 0 && self.weights[tile] > best_weight {
+                best = tile;
+                best_weight = self.weights[tile];
+            }
+        }
+        best
+    }
+}
+
+impl Default for Wfc {
+    /// Creates generator with rules for coastlines and climate
+    fn default() -> Wfc {
+        let mut gen = Wfc::new();
+        gen.set_weight(HexType::Ocean, 6.0);
+        gen.set_weight(HexType::Water, 2.0);
+        gen.set_weight(HexType::Field, 3.0);
+        gen.set_weight(HexType::Forest, 3.0);
+        gen.set_weight(HexType::Grassland, 2.0);
+        gen.set_weight(HexType::Desert, 1.0);
+        gen.set_weight(HexType::Jungle, 1.0);
+        gen.set_weight(HexType::Tundra, 1.0);
+        gen.set_weight(HexType::Mountain, 1.0);
+        gen.set_weight(HexType::Swamp, 0.5);
+        gen.set_weight(HexType::Ice, 0.5);
+        gen.set_weight(HexType::Impassable, 0.2);
+
+        // always have water between ocean and land
+        let land = [
+            HexType::Field, HexType::Forest, HexType::Desert, HexType::Tundra, HexType::Mountain,
+            HexType::Impassable, HexType::Jungle, HexType::Swamp, HexType::Grassland
+        ];
+        for land_type in &land {
+            gen.forbid(HexType::Ocean, *land_type);
+        }
+
+        // keep climate zones apart
+        gen.forbid(HexType::Desert, HexType::Ice);
+        gen.forbid(HexType::Desert, HexType::Tundra);
+        gen.forbid(HexType::Desert, HexType::Swamp);
+        gen.forbid(HexType::Jungle, HexType::Ice);
+        gen.forbid(HexType::Jungle, HexType::Tundra);
+        gen
+    }
+}
+
+impl MapGen for Wfc {
+    fn generate(&self, hex_map: &mut HexMap) {
+        let seed = match self.seed {
+            Some(val) => val,
+            None => random::<u32>()
+        };
+        debug_println!("seed: {:?}", seed);
+
+        let mut rng = StdRng::from_seed(self.seed_to_rng_seed(seed));
+
+        let mut state = WaveState::new(self, hex_map);
+
+        let constrained = state.apply_constraints(self, hex_map);
+
+        if constrained {
+            state.collapse(self, &mut rng);
+        } else {
+            debug_println!("Constraints are contradicting each other");
+        }
+
+        // fill all hexes which were not collapsed
+        for (hex, domain) in hex_map.field.iter_mut().zip(state.domains.iter()) {
+            let tile = if domain.count_ones() == 1 {
+                domain.trailing_zeros() as usize
+            } else {
+                self.best_tile(*domain)
+            };
+            hex.terrain_type = HexType::from(tile as i32);
+        }
+    }
+
+    fn set_seed(&mut self, seed: u32) {
+        self.seed = Some(seed);
+    }
+
+    fn reset_seed(&mut self) {
+        self.seed = None;
+    }
+}
+
+/// State of the collapse with history used for backtracking
+struct WaveState {
+    domains: Vec<u16>,
+    neighbours: Vec<Vec<usize>>,
+    /// Changed domains with their old values
+    trail: Vec<(usize, u16)>,
+    /// Collapsed hex, chosen tile and trail length before the decision
+    decisions: Vec<(usize, usize, usize)>,
+    queue: BinaryHeap<Candidate>,
+}
+
+impl WaveState {
+    fn new(gen: &Wfc, hex_map: &HexMap) -> WaveState {
+        let mut full_domain = 0;
+        for (tile, weight) in gen.weights.iter().enumerate() {
+            if *weight > 0.0 {
+                full_domain |= 1 << tile;
+            }
+        }
+
+        let neighbours = hex_map.field.iter().map(|hex| {
+            let coords = if gen.wrap_map {
+                hex.get_neighbours(hex_map)
+            } else {
+                hex.get_neighbours_bounded(hex_map)
+            };
+            coords.iter().filter_map(|&(x, y)| hex_map.coords_to_index(x, y)).collect()
+        }).collect();
+
+        WaveState{
+            domains: vec![full_domain; hex_map.field.len()],
+            neighbours,
+            trail: Vec::new(),
+            decisions: Vec::new(),
+            queue: BinaryHeap::new()
+        }
+    }
+
+    /// Sets constrained hexes and propagates them, returns `false` on contradiction
+    fn apply_constraints(&mut self, gen: &Wfc, hex_map: &HexMap) -> bool {
+        let mut changed = Vec::with_capacity(gen.constraints.len());
+        for &(x, y, hex_type) in &gen.constraints {
+            let index = match hex_map.get_hex(x, y) {
+                Some(hex) if hex.x == x && hex.y == y => hex_map.coords_to_index(x, y).unwrap(),
+                _ => continue
+            };
+            let tile = 1 << Wfc::tile_index(hex_type);
+            if self.domains[index] & tile == 0 && self.domains[index].count_ones() == 1 {
+                // two constraints on the same hex
+                return false;
+            }
+            self.domains[index] = tile;
+            changed.push(index);
+        }
+        // propagation is not undone, so trail can be discarded
+        let result = changed.iter().all(|&index| self.propagate(gen, index));
+        self.trail.clear();
+        result
+    }
+
+    fn set_domain(&mut self, index: usize, domain: u16) {
+        self.trail.push((index, self.domains[index]));
+        self.domains[index] = domain;
+    }
+
+    /// Propagates adjacency rules from changed hex, returns `false` on contradiction
+    fn propagate(&mut self, gen: &Wfc, start: usize) -> bool {
+        let mut stack = vec![start];
+        while let Some(index) = stack.pop() {
+            let domain = self.domains[index];
+            let mut allowed = 0;
+            for (tile, rules) in gen.rules.iter().enumerate() {
+                if domain & (1 << tile) != 0 {
+                    allowed |= rules;
+                }
+            }
+            for i in 0..self.neighbours[index].len() {
+                let neighbour = self.neighbours[index][i];
+                let old = self.domains[neighbour];
+                let new = old & allowed;
+                if new == old {
+                    continue;
+                }
+                self.set_domain(neighbour, new);
+                if new == 0 {
+                    return false;
+                }
+                stack.push(neighbour);
+            }
+        }
+        true
+    }
+
+    fn push_candidate(&mut self, gen: &Wfc, index: usize, rng: &mut StdRng) {
+        let domain = self.domains[index];
+        if domain.count_ones() > 1 {
+            // small noise breaks ties randomly
+            let entropy = gen.entropy(domain) + rng.gen::<f32>() * 1e-3;
+            self.queue.push(Candidate{entropy, index, domain});
+        }
+    }
+
+    /// Returns to state before the last decision, returns `false` if there is no decision left
+    fn backtrack(&mut self, gen: &Wfc, rng: &mut StdRng) -> bool {
+        while let Some((index, tile, trail_len)) = self.decisions.pop() {
+            while self.trail.len() > trail_len {
+                let (changed, old) = self.trail.pop().unwrap();
+                self.domains[changed] = old;
+                self.push_candidate(gen, changed, rng);
+            }
+            // remove failed tile, this change belongs to the previous decision
+            let domain = self.domains[index] & !(1 << tile);
+            if domain == 0 {
+                continue;
+            }
+            let trail_start = self.trail.len();
+            self.set_domain(index, domain);
+            if self.propagate(gen, index) {
+                for i in trail_start..self.trail.len() {
+                    let changed = self.trail[i].0;
+                    self.push_candidate(gen, changed, rng);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    fn collapse(&mut self, gen: &Wfc, rng: &mut StdRng) {
+        for index in 0..self.domains.len() {
+            self.push_candidate(gen, index, rng);
+        }
+
+        let mut backtracks = 0;
+
+        while let Some(candidate) = self.queue.pop() {
+            // skip outdated candidates
+            if self.domains[candidate.index] != candidate.domain || candidate.domain.count_ones() < 2 {
+                continue;
+            }
+
+            let tile = gen.pick_tile(candidate.domain, rng);
+            self.decisions.push((candidate.index, tile, self.trail.len()));
+            self.set_domain(candidate.index, 1 << tile);
+
+            let trail_start = self.trail.len();
+            if self.propagate(gen, candidate.index) {
+                // neighbours with reduced domain have new entropy
+                for i in trail_start..self.trail.len() {
+                    let changed = self.trail[i].0;
+                    self.push_candidate(gen, changed, rng);
+                }
+                continue;
+            }
+
+            backtracks += 1;
+            if backtracks > gen.max_backtracks || !self.backtrack(gen, rng) {
+                debug_println!("Wave function collapse failed after {} backtracks", backtracks);
+                return;
+            }
+        }
+    }
+}
+
+/// Hex waiting for collapse, ordered by the lowest entropy
+struct Candidate {
+    entropy: f32,
+    index: usize,
+    domain: u16,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed for min-heap
+        other.entropy.partial_cmp(&self.entropy).unwrap_or(Ordering::Equal).then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn respects_rules() {
+        let mut hexmap = HexMap::new(30, 20);
+        let mut gen = Wfc::default();
+        gen.set_seed(42);
+        gen.add_constraint(10, 5, HexType::Desert);
+        gen.generate(&mut hexmap);
+
+        assert_eq!(HexType::Desert, hexmap.get_hex(10, 5).unwrap().terrain_type);
+        for hex in &hexmap.field {
+            for (x, y) in hex.get_neighbours(&hexmap) {
+                let other = hexmap.get_hex(x, y).unwrap();
+                assert!(gen.is_allowed(hex.terrain_type, other.terrain_type));
+            }
+        }
+    }
+}
@@ -18,6 +18,7 @@ pub struct Hex {
     pub y: i32,
     pub terrain_type: HexType,
     pub decor: Decor,
+    pub resource: Option<Resource>,
 }
 
 /// This is roughly ratio of hexagon height to width
@@ -26,7 +27,7 @@ pub const RATIO: f32 = 1.154_700_538_38;
 impl Hex {
     /// Creates new `Hex` from specific coordinates with default `terrain_type`
     pub fn from_coords(x: i32, y: i32) -> Hex {
-        Hex{x, y, terrain_type: HexType::Water, decor: Decor::empty()}
+        Hex{x, y, terrain_type: HexType::Water, decor: Decor::empty(), resource: None}
     }
 
     /// Returns center of the `Hex`
@@ -100,6 +101,14 @@ impl Hex {
         neighbours
     }
 
+    /// Returns vector of `Hex` tiles next to specified `Hex` without wrapping on the X axis
+    pub fn get_neighbours_bounded(&self, hexmap: &HexMap) -> Vec<(i32, i32)> {
+        let mut neighbours = self.get_neighbours(hexmap);
+        // wrapped coordinates are changed by `unwrap_coords`, so only keep the ones next to this hex
+        neighbours.retain(|&(x, y)| (x - self.x).abs() <= 1 && (x + y - self.x - self.y).abs() <= 1);
+        neighbours
+    }
+
     /// Fixes coordinates which are out of bounds 
     pub fn unwrap_coords(x: i32, y: i32, size_x: u32) -> (i32, i32) {
         let mut new_x = x;
@@ -148,7 +157,7 @@ impl Hex {
 
  impl Default for Hex {
     fn default() -> Self {
-        Hex{x:0, y: 0, terrain_type: HexType::Water, decor: Decor::empty()}
+        Hex{x:0, y: 0, terrain_type: HexType::Water, decor: Decor::empty(), resource: None}
     }
 }
 
@@ -263,6 +272,7 @@ impl HexType {
 }
 
 bitflags! {
+    /// Additional features on specific `Hex`, multiple can be combined
     #[derive(Serialize)]
     pub struct Decor: u8 {
         const RIVER   = 0b00000001;
@@ -271,6 +281,8 @@ bitflags! {
         const ROAD    = 0b00001000;
         const RUIN    = 0b00010000;
         const HILL    = 0b00100000;
+        /// Ice sheet on land, distinguishes glaciers from sea ice of the same `HexType`
+        const GLACIER = 0b01000000;
     }
 }
 
@@ -280,6 +292,28 @@ impl Default for Decor {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+/// Strategic or luxury resource on specific `Hex`
+pub enum Resource {
+    Iron,
+    Gold,
+    Horses,
+    Fish,
+    Spices,
+}
+
+impl From<Resource> for String {
+    fn from(resource: Resource) -> String {
+        match resource {
+            Resource::Iron => String::from("Iron"),
+            Resource::Gold => String::from("Gold"),
+            Resource::Horses => String::from("Horses"),
+            Resource::Fish => String::from("Fish"),
+            Resource::Spices => String::from("Spices"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
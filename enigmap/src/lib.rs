@@ -19,6 +19,7 @@
 
 mod hexmap;
 mod hex;
+mod selection;
 
 #[macro_use]
 mod utils;
@@ -29,8 +30,15 @@ pub mod prelude {
     pub use crate::generators::MapGen;
 }
 
-pub use crate::hex::{Hex, HexType, RATIO, HEX_TYPE_STRINGS};
+pub use crate::hex::{Hex, HexType, Decor, Resource, RATIO, HEX_TYPE_STRINGS};
 pub use crate::hexmap::HexMap;
+pub use crate::selection::{Selection, blend_border};
 
 /// Map generators
-pub mod generators;
\ No newline at end of file
+pub mod generators;
+
+/// Chunked maps of unlimited size
+pub mod world;
+
+/// Passes applicable on already generated maps
+pub mod passes;
\ No newline at end of file
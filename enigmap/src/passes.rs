@@ -0,0 +1,162 @@
+use std::collections::VecDeque;
+
+use crate::hexmap::HexMap;
+use crate::hex::{Hex, HexType, Decor};
+
+mod coast;
+mod cryosphere;
+mod lakes;
+mod mountains;
+mod resources;
+mod scatter;
+
+pub use self::coast::{Coast, CoastBand};
+pub use self::cryosphere::{Cryosphere, IceKind};
+pub use self::lakes::Lakes;
+pub use self::mountains::Mountains;
+pub use self::resources::{Resources, ResourceRule};
+pub use self::scatter::{Scatter, ScatterRule};
+
+/// Trait for passes which can be used after any `MapGen`
+pub trait Pass {
+    /// Applies pass on the map
+    ///
+    /// Same seed and map always produce the same result
+    fn apply(&self, hex_map: &mut HexMap, seed: u32);
+}
+
+/// Returns `true` for `Water`, `Ocean` and `Ice` which is not a glacier
+pub(crate) fn is_sea(hex: &Hex) -> bool {
+    match hex.terrain_type {
+        HexType::Water | HexType::Ocean => true,
+        HexType::Ice => !hex.decor.contains(Decor::GLACIER),
+        _ => false
+    }
+}
+
+/// Returns indices of hexes in given radius, including the center
+pub(crate) fn spiral_indices(hex_map: &HexMap, hex: &Hex, radius: u32) -> Vec<usize> {
+    if radius == 0 {
+        return checked_index(hex_map, hex.x, hex.y).into_iter().collect();
+    }
+    hex.get_spiral(hex_map, radius).iter().filter_map(|&(x, y)| checked_index(hex_map, x, y)).collect()
+}
+
+/// Returns index of hex with wrapped coordinates, `None` if it is outside of the map
+pub(crate) fn checked_index(hex_map: &HexMap, x: i32, y: i32) -> Option<usize> {
+    if y < 0 || y >= hex_map.size_y as i32 {
+        return None;
+    }
+    let (x, y) = Hex::unwrap_coords(x, y, hex_map.size_x);
+    hex_map.coords_to_index(x, y)
+}
+
+/// Returns indices of neighbours of hex on given index
+pub(crate) fn neighbour_indices(hex_map: &HexMap, index: usize, wrap_map: bool) -> Vec<usize> {
+    let hex = &hex_map.field[index];
+    let neighbours = if wrap_map {
+        hex.get_neighbours(hex_map)
+    } else {
+        hex.get_neighbours_bounded(hex_map)
+    };
+    neighbours.into_iter().filter_map(|(x, y)| hex_map.coords_to_index(x, y)).collect()
+}
+
+/// Returns distance of every hex to the closest source hex, computed by multi-source BFS
+///
+/// Source hexes have distance 0, hexes with no path to any source have `u32::MAX`
+pub(crate) fn distance_field<F>(hex_map: &HexMap, wrap_map: bool, is_source: F) -> Vec<u32>
+    where F: Fn(&Hex) -> bool
+{
+    let mut distances = vec![u32::MAX; hex_map.field.len()];
+    let mut queue = VecDeque::new();
+    for (index, hex) in hex_map.field.iter().enumerate() {
+        if is_source(hex) {
+            distances[index] = 0;
+            queue.push_back(index);
+        }
+    }
+    while let Some(index) = queue.pop_front() {
+        for other in neighbour_indices(hex_map, index, wrap_map) {
+            if distances[other] == u32::MAX {
+                distances[other] = distances[index] + 1;
+                queue.push_back(other);
+            }
+        }
+    }
+    distances
+}
+
+/// Tree of sums used for picking random hexes by weight
+///
+/// Both updates and picks are `O(log n)`
+#[derive(Debug, Clone)]
+pub(crate) struct WeightTree {
+    weights: Vec<f32>,
+    tree: Vec<f32>,
+}
+
+impl WeightTree {
+    pub fn new(weights: Vec<f32>) -> WeightTree {
+        let mut tree = vec![0.0; weights.len() + 1];
+        for (index, weight) in weights.iter().enumerate() {
+            let mut i = index + 1;
+            while i < tree.len() {
+                tree[i] += weight;
+                i += i & i.wrapping_neg();
+            }
+        }
+        WeightTree{weights, tree}
+    }
+
+    pub fn get(&self, index: usize) -> f32 {
+        self.weights[index]
+    }
+
+    pub fn set(&mut self, index: usize, weight: f32) {
+        let diff = weight - self.weights[index];
+        self.weights[index] = weight;
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += diff;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    pub fn total(&self) -> f32 {
+        let mut sum = 0.0;
+        let mut i = self.weights.len();
+        while i > 0 {
+            sum += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+
+    /// Returns index for value in range `<0; total)`, skipping zero weights
+    pub fn find(&self, value: f32) -> Option<usize> {
+        if self.weights.is_empty() || self.total() <= 0.0 {
+            return None;
+        }
+        let mut remaining = value;
+        let mut position = 0;
+        let mut step = self.weights.len().next_power_of_two();
+        while step > 0 {
+            let next = position + step;
+            if next < self.tree.len() && self.tree[next] <= remaining {
+                remaining -= self.tree[next];
+                position = next;
+            }
+            step >>= 1;
+        }
+        // rounding errors can point past the last non-zero weight
+        let mut index = position.min(self.weights.len() - 1);
+        while self.weights[index] <= 0.0 {
+            if index == 0 {
+                return self.weights.iter().position(|&weight| weight > 0.0);
+            }
+            index -= 1;
+        }
+        Some(index)
+    }
+}
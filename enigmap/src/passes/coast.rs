@@ -0,0 +1,178 @@
+use noise::{Perlin, NoiseFn, Seedable};
+
+use crate::hexmap::HexMap;
+use crate::hex::HexType;
+use crate::passes::{Pass, distance_field, is_sea};
+use crate::utils::map_field;
+
+/// Classification of hex by distance to coast
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoastBand {
+    Land,
+    /// Water right next to land
+    Shallow,
+    /// Continental shelf
+    Shelf,
+    /// Open ocean
+    Deep,
+}
+
+/// Pass which turns `Ocean` near land into shallow water and continental shelf
+///
+/// `Water`, `Ocean` and `Ice` are treated as sea, everything else including glaciers marked by `Decor::GLACIER` is land.
+/// Only `Ocean` hexes are changed, so lakes and ice stay untouched.
+/// The shelf stays `Ocean` by default, set `shelf_type` to make it visible.
+/// ## Usage
+/// ```
+/// use enigmap::{prelude::*, HexType, generators::Circle, passes::{Pass, Coast}};
+///
+/// let mut hexmap = HexMap::new(100, 75);
+/// let gen = Circle::new_optimized(&hexmap);
+/// gen.generate(&mut hexmap);
+///
+/// let coast = Coast{shelf_distance: 6, shelf_type: HexType::Water, ..Default::default()};
+/// coast.apply(&mut hexmap, 42);
+/// let distances = coast.distances(&hexmap);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Coast {
+    /// Maximal distance of shallow water from land, always at least 1
+    pub shallow_distance: u32,
+    /// Maximal distance of continental shelf from land
+    pub shelf_distance: u32,
+    /// Terrain used for the shelf, `Ocean` keeps the shelf unchanged
+    pub shelf_type: HexType,
+    /// Maximal change of the shelf border in hexes
+    pub jitter: f32,
+    /// Scale of noise used for the shelf border
+    pub noise_scale: f64,
+    /// Whether distances wrap around the X axis
+    pub wrap_map: bool,
+}
+
+impl Coast {
+    /// Returns distance to coast for every hex in `HexMap::field`
+    ///
+    /// Sea hexes get distance to the closest land, land hexes get distance to the closest sea.
+    /// Hexes on the coastline have distance 1, `u32::MAX` is used when there is no land or no sea.
+    pub fn distances(&self, hex_map: &HexMap) -> Vec<u32> {
+        let to_land = distance_field(hex_map, self.wrap_map, |hex| !is_sea(hex));
+        let to_sea = distance_field(hex_map, self.wrap_map, is_sea);
+        to_land.into_iter().zip(to_sea).map(|(land, sea)| land.max(sea)).collect()
+    }
+
+    /// Returns coast band of every hex in `HexMap::field`
+    pub fn bands(&self, hex_map: &HexMap, seed: u32) -> Vec<CoastBand> {
+        let noise = Perlin::new().set_seed(seed);
+        let to_land = distance_field(hex_map, self.wrap_map, |hex| !is_sea(hex));
+        map_field(&hex_map.field, |index, hex| {
+            let distance = to_land[index];
+            if distance == 0 {
+                return CoastBand::Land;
+            }
+            if distance <= self.shallow_distance.max(1) {
+                return CoastBand::Shallow;
+            }
+            let (center_x, center_y) = hex.center();
+            let noise_val = noise.get([f64::from(center_x) * self.noise_scale, f64::from(center_y) * self.noise_scale]) as f32;
+            if (distance as f32 + noise_val * self.jitter) <= self.shelf_distance as f32 {
+                CoastBand::Shelf
+            } else {
+                CoastBand::Deep
+            }
+        })
+    }
+}
+
+impl Default for Coast {
+    fn default() -> Coast {
+        Coast{shallow_distance: 1, shelf_distance: 4, shelf_type: HexType::Ocean, jitter: 1.5, noise_scale: 0.1, wrap_map: true}
+    }
+}
+
+impl Pass for Coast {
+    fn apply(&self, hex_map: &mut HexMap, seed: u32) {
+        let bands = self.bands(hex_map, seed);
+        for (hex, band) in hex_map.field.iter_mut().zip(bands) {
+            if hex.terrain_type != HexType::Ocean {
+                continue;
+            }
+            hex.terrain_type = match band {
+                CoastBand::Shallow => HexType::Water,
+                CoastBand::Shelf => self.shelf_type,
+                _ => continue
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::{Hex, Decor};
+
+    #[test]
+    fn bands_follow_distance() {
+        let mut hexmap = HexMap::new(30, 20);
+        hexmap.fill(HexType::Ocean);
+        hexmap.get_hex_mut(10, 10).unwrap().terrain_type = HexType::Field;
+        let coast = Coast{shallow_distance: 2, shelf_distance: 4, shelf_type: HexType::Swamp, jitter: 0.0, wrap_map: false, ..Default::default()};
+        let distances = coast.distances(&hexmap);
+        coast.apply(&mut hexmap, 1);
+
+        let land = Hex::from_coords(10, 10);
+        for (hex, distance) in hexmap.field.iter().zip(distances) {
+            let expected = match hex.distance_between(&land) {
+                0 => HexType::Field,
+                1..=2 => HexType::Water,
+                3..=4 => HexType::Swamp,
+                _ => HexType::Ocean
+            };
+            assert_eq!(expected, hex.terrain_type);
+            assert_eq!(hex.distance_between(&land).max(1), distance);
+        }
+    }
+
+    #[test]
+    fn glaciers_are_land() {
+        let mut hexmap = HexMap::new(30, 20);
+        hexmap.fill(HexType::Ocean);
+        let glacier = hexmap.get_hex_mut(10, 10).unwrap();
+        glacier.terrain_type = HexType::Ice;
+        glacier.decor.insert(Decor::GLACIER);
+        hexmap.get_hex_mut(20, 10).unwrap().terrain_type = HexType::Ice;
+        let coast = Coast{jitter: 0.0, wrap_map: false, ..Default::default()};
+        coast.apply(&mut hexmap, 1);
+
+        // only the glacier has shallow water around it, sea ice does not
+        let glacier = Hex::from_coords(10, 10);
+        for hex in &hexmap.field {
+            let expected = match hex.distance_between(&glacier) {
+                0 => HexType::Ice,
+                1 => HexType::Water,
+                _ if (hex.x, hex.y) == (20, 10) => HexType::Ice,
+                _ => HexType::Ocean
+            };
+            assert_eq!(expected, hex.terrain_type);
+        }
+    }
+
+    #[test]
+    fn default_shelf_differs_from_shallow() {
+        let mut hexmap = HexMap::new(30, 20);
+        hexmap.fill(HexType::Ocean);
+        hexmap.get_hex_mut(10, 10).unwrap().terrain_type = HexType::Field;
+        let coast = Coast{jitter: 0.0, wrap_map: false, ..Default::default()};
+        let bands = coast.bands(&hexmap, 1);
+        coast.apply(&mut hexmap, 1);
+
+        assert!(bands.contains(&CoastBand::Shelf));
+        for (hex, band) in hexmap.field.iter().zip(bands) {
+            match band {
+                CoastBand::Shallow => assert_eq!(HexType::Water, hex.terrain_type),
+                CoastBand::Shelf | CoastBand::Deep => assert_eq!(HexType::Ocean, hex.terrain_type),
+                CoastBand::Land => assert_eq!(HexType::Field, hex.terrain_type),
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use noise::{Perlin, NoiseFn, Seedable};
+
+use crate::hexmap::HexMap;
+use crate::hex::{HexType, Decor};
+use crate::passes::{Pass, is_sea};
+use crate::utils::map_field;
+
+/// Kind of ice on specific hex
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IceKind {
+    /// Frozen sea
+    SeaIce,
+    /// Ice sheet on land
+    Glacier,
+    /// Cold land around glaciers
+    Tundra,
+    None,
+}
+
+/// Pass which creates polar ice caps from temperature
+///
+/// Sea freezes into sea ice, land gets glaciers surrounded by `Tundra`.
+/// Both are `Ice` by default, glaciers are marked with `Decor::GLACIER`.
+/// Temperature goes from 0 on the poles to 1 on the equator.
+/// `Water`, `Ocean` and `Ice` without `Decor::GLACIER` are sea, existing `Ice` is kept.
+/// ## Usage
+/// ```
+/// use enigmap::{prelude::*, generators::Circle, passes::{Pass, Cryosphere}};
+///
+/// let mut hexmap = HexMap::new(100, 75);
+/// let gen = Circle::new_optimized(&hexmap);
+/// gen.generate(&mut hexmap);
+///
+/// // northern winter
+/// let cryosphere = Cryosphere{season: 1.0, ..Default::default()};
+/// cryosphere.apply(&mut hexmap, 42);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Cryosphere {
+    /// Temperature under which is the north covered by ice
+    pub north_cap: f32,
+    /// Temperature under which is the south covered by ice
+    pub south_cap: f32,
+    /// Ratio of sea ice cap size to glacier cap size
+    pub sea_ice_extent: f32,
+    /// Temperature width of `Tundra` around glaciers
+    pub tundra_width: f32,
+    /// Season in range `<-1; 1>`, positive values are northern winter
+    pub season: f32,
+    /// How much do caps grow and shrink with season, relative to their size
+    pub seasonal_extent: f32,
+    /// Maximal change of temperature by noise
+    pub jitter: f32,
+    /// Scale of temperature noise
+    pub noise_scale: f64,
+    /// Terrain used for sea ice
+    pub sea_ice_type: HexType,
+    /// Terrain used for glaciers, glaciers also get `Decor::GLACIER`
+    pub glacier_type: HexType,
+}
+
+impl Cryosphere {
+    /// Returns temperature of every hex in `HexMap::field` by its latitude
+    pub fn temperatures(&self, hex_map: &HexMap, seed: u32) -> Vec<f32> {
+        let noise = Perlin::new().set_seed(seed);
+        map_field(&hex_map.field, |_, hex| {
+            let (center_x, center_y) = hex.center();
+            let noise_val = noise.get([f64::from(center_x) * self.noise_scale, f64::from(center_y) * self.noise_scale]) as f32;
+            let latitude = 1.0 - (center_y / hex_map.absolute_size_y - 0.5).abs() * 2.0;
+            latitude + noise_val * self.jitter
+        })
+    }
+
+    /// Returns ice kind of every hex in `HexMap::field` from given temperature
+    /// # Panics
+    /// when `temperature` has different length than `HexMap::field`
+    pub fn classify(&self, hex_map: &HexMap, temperature: &[f32]) -> Vec<IceKind> {
+        if temperature.len() != hex_map.field.len() {
+            panic!("temperature has different size than the map");
+        }
+        let north_cap = (self.north_cap * (1.0 + self.season * self.seasonal_extent)).max(0.0);
+        let south_cap = (self.south_cap * (1.0 - self.season * self.seasonal_extent)).max(0.0);
+
+        hex_map.field.iter().zip(temperature).map(|(hex, &temp)| {
+            let cap = if hex.y < hex_map.size_y as i32 / 2 { north_cap } else { south_cap };
+            if is_sea(hex) {
+                if temp < cap * self.sea_ice_extent { IceKind::SeaIce } else { IceKind::None }
+            } else {
+                match hex.terrain_type {
+                    HexType::Mountain | HexType::Impassable => IceKind::None,
+                    _ => {
+                        if temp < cap {
+                            IceKind::Glacier
+                        } else if temp < cap + self.tundra_width {
+                            IceKind::Tundra
+                        } else {
+                            IceKind::None
+                        }
+                    }
+                }
+            }
+        }).collect()
+    }
+
+    /// Applies pass with given temperature of every hex in `HexMap::field`
+    /// # Panics
+    /// when `temperature` has different length than `HexMap::field`
+    pub fn apply_with(&self, hex_map: &mut HexMap, temperature: &[f32]) {
+        let kinds = self.classify(hex_map, temperature);
+        for (hex, kind) in hex_map.field.iter_mut().zip(kinds) {
+            match kind {
+                IceKind::SeaIce => hex.terrain_type = self.sea_ice_type,
+                IceKind::Glacier => {
+                    hex.terrain_type = self.glacier_type;
+                    hex.decor.insert(Decor::GLACIER);
+                },
+                IceKind::Tundra => {
+                    hex.terrain_type = HexType::Tundra;
+                    hex.decor.remove(Decor::GLACIER);
+                },
+                IceKind::None => {}
+            }
+        }
+    }
+}
+
+impl Default for Cryosphere {
+    fn default() -> Cryosphere {
+        Cryosphere{
+            north_cap: 0.12,
+            south_cap: 0.12,
+            sea_ice_extent: 1.3,
+            tundra_width: 0.08,
+            season: 0.0,
+            seasonal_extent: 0.4,
+            jitter: 0.04,
+            noise_scale: 0.1,
+            sea_ice_type: HexType::Ice,
+            glacier_type: HexType::Ice
+        }
+    }
+}
+
+impl Pass for Cryosphere {
+    fn apply(&self, hex_map: &mut HexMap, seed: u32) {
+        let temperature = self.temperatures(hex_map, seed);
+        self.apply_with(hex_map, &temperature);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caps_and_seasons() {
+        let mut hexmap = HexMap::new(20, 40);
+        for hex in &mut hexmap.field {
+            hex.terrain_type = if hex.x + hex.y / 2 < 10 { HexType::Ocean } else { HexType::Field };
+        }
+        let cryosphere = Cryosphere{season: 1.0, seasonal_extent: 0.5, jitter: 0.0, ..Default::default()};
+        let temperature = cryosphere.temperatures(&hexmap, 0);
+        let kinds = cryosphere.classify(&hexmap, &temperature);
+
+        // poles are frozen, equator is not
+        let last_row = hexmap.field.len() - 20;
+        assert_eq!(IceKind::SeaIce, kinds[0]);
+        assert_eq!(IceKind::Glacier, kinds[19]);
+        assert_eq!(IceKind::SeaIce, kinds[last_row]);
+        assert_eq!(IceKind::Glacier, kinds[last_row + 19]);
+        assert!(kinds[20 * 20..21 * 20].iter().all(|&kind| kind == IceKind::None));
+
+        // winter on the north
+        let count = |range: std::ops::Range<usize>| kinds[range].iter().filter(|&&kind| kind != IceKind::None).count();
+        assert!(count(0..400) > count(400..800));
+    }
+
+    #[test]
+    fn glaciers_differ_from_sea_ice() {
+        let mut hexmap = HexMap::new(20, 40);
+        for hex in &mut hexmap.field {
+            hex.terrain_type = if hex.x + hex.y / 2 < 10 { HexType::Ocean } else { HexType::Field };
+        }
+        let cryosphere = Cryosphere{jitter: 0.0, ..Default::default()};
+        cryosphere.apply(&mut hexmap, 0);
+
+        let last_row = hexmap.field.len() - 20;
+        for &index in &[0, last_row] {
+            assert_eq!(HexType::Ice, hexmap.field[index].terrain_type);
+            assert!(!hexmap.field[index].decor.contains(Decor::GLACIER));
+        }
+        for &index in &[19, last_row + 19] {
+            assert_eq!(HexType::Ice, hexmap.field[index].terrain_type);
+            assert!(hexmap.field[index].decor.contains(Decor::GLACIER));
+        }
+
+        // glaciers stay on land when the pass is applied again
+        let temperature = cryosphere.temperatures(&hexmap, 0);
+        let kinds = cryosphere.classify(&hexmap, &temperature);
+        assert_eq!(IceKind::SeaIce, kinds[0]);
+        assert_eq!(IceKind::Glacier, kinds[19]);
+    }
+}
@@ -0,0 +1,288 @@
+use noise::{Fbm, Perlin, NoiseFn, Seedable};
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::hexmap::HexMap;
+use crate::hex::{Decor, HexType};
+use crate::passes::{Pass, neighbour_indices, is_sea};
+use crate::utils::map_field;
+
+/// Pass which fills elevation depressions with lakes
+///
+/// Depressions are filled up to their spill point, lakes get `Decor::RIVER` outflow to the sea or map edge
+/// and low humid hexes around lakes become `Swamp`.
+/// `Pass::apply` uses noise for elevation and humidity, use `Lakes::apply_with` for own values.
+/// ## Usage
+/// ```
+/// use enigmap::{prelude::*, generators::Inland, passes::{Pass, Lakes}};
+///
+/// let mut hexmap = HexMap::new(100, 75);
+/// let gen = Inland::default();
+/// gen.generate(&mut hexmap);
+///
+/// let lakes = Lakes{min_size: 4, ..Default::default()};
+/// lakes.apply(&mut hexmap, 42);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Lakes {
+    /// Scale of elevation and humidity noise
+    pub noise_scale: f64,
+    /// Minimal depth of depression which becomes lake
+    pub min_depth: f32,
+    /// Minimal number of hexes in lake, smaller lakes are not created
+    pub min_size: u32,
+    /// Maximal distance of swamps from lakes
+    pub swamp_distance: u32,
+    /// Minimal humidity of swamps, in range `<0; 1>`
+    pub swamp_humidity: f32,
+    /// Maximal height of swamps above the lake surface
+    pub swamp_height: f32,
+    /// Whether lakes get rivers flowing out of them
+    pub outflows: bool,
+    pub wrap_map: bool,
+}
+
+impl Lakes {
+    /// Creates lakes from given elevation and humidity of every hex in `HexMap::field`
+    ///
+    /// `Water`, `Ocean` and `Ice` hexes and hexes on the map edge are the sea, where water can flow.
+    /// # Panics
+    /// when `elevation` or `humidity` have different length than `HexMap::field`
+    pub fn apply_with(&self, hex_map: &mut HexMap, elevation: &[f32], humidity: &[f32]) {
+        if elevation.len() != hex_map.field.len() || humidity.len() != hex_map.field.len() {
+            panic!("elevation or humidity has different size than the map");
+        }
+
+        let (filled, flow) = self.fill_depressions(hex_map, elevation);
+
+        // find contiguous lakes
+        let mut lake_ids = vec![None; hex_map.field.len()];
+        let mut lakes = Vec::new();
+        for start in 0..hex_map.field.len() {
+            if lake_ids[start].is_some() || !self.is_lake(hex_map, start, elevation, &filled) {
+                continue;
+            }
+            let id = lakes.len();
+            let mut lake = vec![start];
+            lake_ids[start] = Some(id);
+            let mut next = 0;
+            while next < lake.len() {
+                for other in neighbour_indices(hex_map, lake[next], self.wrap_map) {
+                    if lake_ids[other].is_none() && self.is_lake(hex_map, other, elevation, &filled) {
+                        lake_ids[other] = Some(id);
+                        lake.push(other);
+                    }
+                }
+                next += 1;
+            }
+            lakes.push(lake);
+        }
+
+        let mut lake_level = vec![None; hex_map.field.len()];
+        for lake in lakes.iter().filter(|lake| lake.len() >= self.min_size as usize) {
+            for &index in lake {
+                hex_map.field[index].terrain_type = HexType::Water;
+                hex_map.field[index].decor.remove(Decor::RIVER);
+                lake_level[index] = Some(filled[index]);
+            }
+            if self.outflows {
+                self.outflow(hex_map, lake, &flow);
+            }
+        }
+
+        self.swamp_pass(hex_map, elevation, humidity, &lake_level);
+    }
+
+    /// Priority flood from the sea, returns filled elevation and direction of flow for every hex
+    fn fill_depressions(&self, hex_map: &HexMap, elevation: &[f32]) -> (Vec<f32>, Vec<Option<usize>>) {
+        let mut filled = elevation.to_vec();
+        let mut flow = vec![None; hex_map.field.len()];
+        let mut visited = vec![false; hex_map.field.len()];
+        let mut queue = BinaryHeap::new();
+
+        for index in 0..hex_map.field.len() {
+            if self.is_sea(hex_map, index) {
+                visited[index] = true;
+                queue.push(Cell{level: filled[index], index});
+            }
+        }
+
+        while let Some(Cell{level, index}) = queue.pop() {
+            for other in neighbour_indices(hex_map, index, self.wrap_map) {
+                if visited[other] {
+                    continue;
+                }
+                visited[other] = true;
+                filled[other] = filled[other].max(level);
+                flow[other] = Some(index);
+                queue.push(Cell{level: filled[other], index: other});
+            }
+        }
+
+        (filled, flow)
+    }
+
+    /// Marks river from the spill point of the lake to the sea
+    fn outflow(&self, hex_map: &mut HexMap, lake: &[usize], flow: &[Option<usize>]) {
+        // the only lake hex flowing outside of the lake is next to the spill point
+        let mut current = match lake.iter().filter_map(|&index| flow[index]).find(|&next| hex_map.field[next].terrain_type != HexType::Water) {
+            Some(index) => index,
+            None => return
+        };
+        loop {
+            if self.is_sea(hex_map, current) {
+                break;
+            }
+            hex_map.field[current].decor.insert(Decor::RIVER);
+            current = match flow[current] {
+                Some(next) => next,
+                None => break
+            };
+        }
+    }
+
+    fn swamp_pass(&self, hex_map: &mut HexMap, elevation: &[f32], humidity: &[f32], lake_level: &[Option<f32>]) {
+        if self.swamp_distance == 0 {
+            return;
+        }
+        // spread surface level of the closest lake
+        let mut distances = vec![u32::MAX; hex_map.field.len()];
+        let mut levels = lake_level.to_vec();
+        let mut queue = VecDeque::new();
+        for (index, level) in lake_level.iter().enumerate() {
+            if level.is_some() {
+                distances[index] = 0;
+                queue.push_back(index);
+            }
+        }
+        while let Some(index) = queue.pop_front() {
+            if distances[index] == self.swamp_distance {
+                continue;
+            }
+            for other in neighbour_indices(hex_map, index, self.wrap_map) {
+                if distances[other] == u32::MAX {
+                    distances[other] = distances[index] + 1;
+                    levels[other] = levels[index];
+                    queue.push_back(other);
+                }
+            }
+        }
+
+        for (index, hex) in hex_map.field.iter_mut().enumerate() {
+            let level = match levels[index] {
+                Some(level) if distances[index] > 0 => level,
+                _ => continue
+            };
+            let suitable = matches!(hex.terrain_type, HexType::Field | HexType::Forest | HexType::Grassland | HexType::Jungle | HexType::Tundra);
+            if suitable && humidity[index] >= self.swamp_humidity && elevation[index] - level <= self.swamp_height {
+                hex.terrain_type = HexType::Swamp;
+            }
+        }
+    }
+
+    fn is_lake(&self, hex_map: &HexMap, index: usize, elevation: &[f32], filled: &[f32]) -> bool {
+        let can_flood = !matches!(hex_map.field[index].terrain_type, HexType::Water | HexType::Ocean | HexType::Ice | HexType::Mountain | HexType::Impassable);
+        can_flood && filled[index] - elevation[index] >= self.min_depth
+    }
+
+    fn is_sea(&self, hex_map: &HexMap, index: usize) -> bool {
+        is_sea(&hex_map.field[index]) || neighbour_indices(hex_map, index, self.wrap_map).len() < 6
+    }
+}
+
+impl Default for Lakes {
+    fn default() -> Lakes {
+        Lakes{
+            noise_scale: 0.08,
+            min_depth: 0.02,
+            min_size: 2,
+            swamp_distance: 1,
+            swamp_humidity: 0.55,
+            swamp_height: 0.05,
+            outflows: true,
+            wrap_map: true
+        }
+    }
+}
+
+impl Pass for Lakes {
+    fn apply(&self, hex_map: &mut HexMap, seed: u32) {
+        let elevation_noise = Fbm::new().set_seed(seed);
+        let humidity_noise = Perlin::new().set_seed(seed.wrapping_add(1));
+
+        let (elevation, humidity): (Vec<f32>, Vec<f32>) = map_field(&hex_map.field, |_, hex| {
+            let (x, y) = hex.center();
+            let point = [f64::from(x) * self.noise_scale, f64::from(y) * self.noise_scale];
+            let bias = match hex.terrain_type {
+                HexType::Mountain => 0.5,
+                HexType::Impassable => 0.8,
+                _ => 0.0
+            };
+            (elevation_noise.get(point) as f32 + bias, (humidity_noise.get(point) as f32 + 1.0) / 2.0)
+        }).into_iter().unzip();
+
+        self.apply_with(hex_map, &elevation, &humidity);
+    }
+}
+
+/// Hex in priority flood, ordered by the lowest level
+struct Cell {
+    level: f32,
+    index: usize,
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed for min-heap
+        other.level.partial_cmp(&self.level).unwrap_or(Ordering::Equal).then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::Hex;
+
+    #[test]
+    fn fills_depression() {
+        let mut hexmap = HexMap::new(20, 20);
+        hexmap.fill(HexType::Field);
+        let center = Hex::from_coords(5, 10);
+        // bowl with rim broken on one side, sloping down to the map edge
+        let elevation = hexmap.field.iter().map(|hex| {
+            match hex.distance_between(&center) {
+                0..=1 => 0.0,
+                2 if hex.x > center.x && hex.y == center.y => 0.3,
+                2 => 0.5,
+                _ => 0.2
+            }
+        }).collect::<Vec<f32>>();
+        let humidity = vec![1.0; hexmap.field.len()];
+        let lakes = Lakes{wrap_map: false, ..Default::default()};
+        lakes.apply_with(&mut hexmap, &elevation, &humidity);
+
+        for hex in &hexmap.field {
+            match hex.distance_between(&center) {
+                0..=1 => assert_eq!(HexType::Water, hex.terrain_type),
+                2 if hex.x > center.x && hex.y == center.y => assert!(hex.decor.contains(Decor::RIVER)),
+                2 => assert_eq!(HexType::Field, hex.terrain_type),
+                _ => assert_ne!(HexType::Water, hex.terrain_type)
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use rand::prelude::*;
+
+use crate::hexmap::HexMap;
+use crate::hex::{Hex, Decor, HexType};
+use crate::passes::{Pass, neighbour_indices, is_sea};
+
+/// Neighbour offsets in clockwise order, so neighbouring directions differ by one
+const DIRECTIONS: [(i32, i32); 6] = [(0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1), (1, 0)];
+
+/// Pass which traces mountain ranges over land
+///
+/// Each range is a random walk forming `Mountain` spine with `Impassable` peaks
+/// and `Decor::HILL` foothills around it. Ranges end at the coast.
+/// ## Usage
+/// ```
+/// use enigmap::{prelude::*, generators::Circle, passes::{Pass, Mountains}};
+///
+/// let mut hexmap = HexMap::new(100, 75);
+/// let mut gen = Circle::new_optimized(&hexmap);
+/// // long and straight ranges
+/// gen.mountains = Mountains{min_length: 12, max_length: 25, curvature: 0.1, ..Default::default()};
+/// gen.generate(&mut hexmap);
+///
+/// // or add more ranges to existing map
+/// Mountains::default().apply(&mut hexmap, 42);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Mountains {
+    /// Ratio of land hexes which become mountains
+    pub coverage: f32,
+    /// Minimal number of hexes in one range
+    pub min_length: u32,
+    /// Maximal number of hexes in one range
+    pub max_length: u32,
+    /// Chance of the range changing direction in each step, in range `<0; 1>`
+    pub curvature: f32,
+    /// Chance of inner range hexes becoming `Impassable`
+    pub peak_chance: f32,
+    /// Width of foothills around ranges
+    pub foothill_width: u32,
+    /// Chance of hex near range getting `Decor::HILL`
+    pub foothill_chance: f32,
+    pub wrap_map: bool,
+}
+
+impl Mountains {
+    /// Returns `true` if mountains can be placed on the hex
+    fn is_land(hex: &Hex) -> bool {
+        !is_sea(hex) && !matches!(hex.terrain_type, HexType::Mountain | HexType::Impassable)
+    }
+
+    /// Traces single range, returns indices of its hexes
+    fn trace_range(&self, hex_map: &mut HexMap, start: usize, rng: &mut StdRng) -> Vec<usize> {
+        let length = rng.gen_range(self.min_length.max(1), self.max_length.max(self.min_length.max(1)) + 1);
+        let mut direction = rng.gen_range(0, DIRECTIONS.len());
+        let mut range = Vec::with_capacity(length as usize);
+        let mut current = start;
+
+        for _ in 0..length {
+            hex_map.field[current].terrain_type = HexType::Mountain;
+            hex_map.field[current].decor.remove(Decor::HILL | Decor::GLACIER);
+            range.push(current);
+
+            if rng.gen::<f32>() < self.curvature {
+                direction = if rng.gen() { (direction + 1) % 6 } else { (direction + 5) % 6 };
+            }
+            current = match self.step(hex_map, current, direction) {
+                Some(next) if Self::is_land(&hex_map.field[next]) => next,
+                _ => break
+            };
+        }
+        range
+    }
+
+    /// Returns index of neighbour in given direction
+    fn step(&self, hex_map: &HexMap, index: usize, direction: usize) -> Option<usize> {
+        let hex = &hex_map.field[index];
+        let (x, y) = (hex.x + DIRECTIONS[direction].0, hex.y + DIRECTIONS[direction].1);
+        if y < 0 || y >= hex_map.size_y as i32 {
+            return None;
+        }
+        let wrapped = Hex::unwrap_coords(x, y, hex_map.size_x);
+        if !self.wrap_map && wrapped != (x, y) {
+            return None;
+        }
+        hex_map.coords_to_index(wrapped.0, wrapped.1)
+    }
+
+    /// Adds hills around the range, `visited` is shared by all ranges and is cleared before returning
+    fn foothills(&self, hex_map: &mut HexMap, range: &[usize], visited: &mut [bool], rng: &mut StdRng) {
+        let mut ring = range.to_vec();
+        let mut touched = range.to_vec();
+        for &index in range {
+            visited[index] = true;
+        }
+        for _ in 0..self.foothill_width {
+            let mut next_ring = Vec::new();
+            for &index in &ring {
+                for other in neighbour_indices(hex_map, index, self.wrap_map) {
+                    if visited[other] {
+                        continue;
+                    }
+                    visited[other] = true;
+                    touched.push(other);
+                    next_ring.push(other);
+                    if Self::is_land(&hex_map.field[other]) && rng.gen::<f32>() < self.foothill_chance {
+                        hex_map.field[other].decor.insert(Decor::HILL);
+                    }
+                }
+            }
+            ring = next_ring;
+        }
+        for index in touched {
+            visited[index] = false;
+        }
+    }
+}
+
+impl Default for Mountains {
+    fn default() -> Mountains {
+        Mountains{
+            coverage: 0.08,
+            min_length: 4,
+            max_length: 12,
+            curvature: 0.3,
+            peak_chance: 0.2,
+            foothill_width: 1,
+            foothill_chance: 0.5,
+            wrap_map: true
+        }
+    }
+}
+
+impl Pass for Mountains {
+    fn apply(&self, hex_map: &mut HexMap, seed: u32) {
+        let mut rng = StdRng::seed_from_u64(u64::from(seed));
+        let land = hex_map.field.iter().filter(|hex| Self::is_land(hex)).count();
+        let target = (land as f32 * self.coverage) as usize;
+
+        let mut visited = vec![false; hex_map.field.len()];
+        let mut placed = 0;
+        let mut attempts = 0;
+        while placed < target && attempts < target + 100 {
+            attempts += 1;
+            let start = rng.gen_range(0, hex_map.field.len());
+            if !Self::is_land(&hex_map.field[start]) {
+                continue;
+            }
+            let range = self.trace_range(hex_map, start, &mut rng);
+            placed += range.len();
+
+            // peaks only inside of the range
+            if range.len() > 2 {
+                for &index in &range[1..range.len() - 1] {
+                    if rng.gen::<f32>() < self.peak_chance {
+                        hex_map.field[index].terrain_type = HexType::Impassable;
+                    }
+                }
+            }
+            self.foothills(hex_map, &range, &mut visited, &mut rng);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_are_connected() {
+        let mut hexmap = HexMap::new(40, 30);
+        hexmap.fill(HexType::Field);
+        let mountains = Mountains{min_length: 8, max_length: 8, curvature: 0.0, ..Default::default()};
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let start = hexmap.coords_to_index(12, 15).unwrap();
+        let range = mountains.trace_range(&mut hexmap, start, &mut rng);
+        assert_eq!(8, range.len());
+        for pair in range.windows(2) {
+            assert_eq!(1, hexmap.field[pair[0]].distance_between(&hexmap.field[pair[1]]));
+        }
+
+        mountains.apply(&mut hexmap, 5);
+        assert!(hexmap.field.iter().any(|hex| hex.terrain_type == HexType::Impassable));
+        assert!(hexmap.field.iter().filter(|hex| hex.decor.contains(Decor::HILL)).all(|hex| hex.terrain_type == HexType::Field));
+    }
+}
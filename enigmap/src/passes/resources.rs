@@ -0,0 +1,222 @@
+use rand::prelude::*;
+
+use crate::hexmap::HexMap;
+use crate::hex::{Hex, HexType, Resource};
+use crate::passes::{Pass, WeightTree, spiral_indices, checked_index};
+
+/// Placement settings of single resource
+#[derive(Debug, Clone)]
+pub struct ResourceRule {
+    pub resource: Resource,
+    /// How likely is the resource to appear on specific `HexType`
+    pub affinities: Vec<(HexType, f32)>,
+    /// Ratio of suitable hexes, i.e., hexes with positive affinity, which get the resource
+    pub density: f32,
+    /// How much are the resources grouped together, in range `<0; 1>`
+    pub clustering: f32,
+}
+
+impl ResourceRule {
+    pub fn new(resource: Resource, affinities: &[(HexType, f32)], density: f32, clustering: f32) -> ResourceRule {
+        ResourceRule{resource, affinities: affinities.to_vec(), density, clustering}
+    }
+
+    /// Returns affinity of the resource to `HexType`
+    pub fn affinity(&self, hex_type: HexType) -> f32 {
+        self.affinities.iter().find(|(affinity_type, _)| *affinity_type == hex_type).map_or(0.0, |(_, affinity)| *affinity)
+    }
+}
+
+/// Pass which places resources by terrain affinity
+///
+/// ## Usage
+/// ```
+/// use enigmap::{prelude::*, Resource, generators::Circle, passes::{Pass, Resources}};
+///
+/// let mut hexmap = HexMap::new(100, 75);
+/// let gen = Circle::new_optimized(&hexmap);
+/// gen.generate(&mut hexmap);
+///
+/// let mut resources = Resources::default();
+/// // every player has iron and horses in 5 hex radius
+/// resources.start_points = vec![(20, 30), (60, 40)];
+/// resources.guaranteed = vec![Resource::Iron, Resource::Horses];
+/// resources.apply(&mut hexmap, 42);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Resources {
+    /// Rules for each placed resource
+    pub rules: Vec<ResourceRule>,
+    /// Minimal distance between two resources
+    pub min_spacing: u32,
+    /// Coordinates of player start points
+    pub start_points: Vec<(i32, i32)>,
+    /// Radius around start points in which guaranteed resources are placed
+    pub fair_radius: u32,
+    /// Resources which must be near every start point
+    pub guaranteed: Vec<Resource>,
+}
+
+impl Resources {
+    /// Clears all resources on the map
+    pub fn clear(hex_map: &mut HexMap) {
+        for hex in &mut hex_map.field {
+            hex.resource = None;
+        }
+    }
+
+    fn place_rule(&self, hex_map: &mut HexMap, rule: &ResourceRule, rng: &mut StdRng) {
+        let weights = hex_map.field.iter().map(|hex| {
+            if hex.resource.is_some() {
+                0.0
+            } else {
+                rule.affinity(hex.terrain_type).max(0.0)
+            }
+        }).collect::<Vec<f32>>();
+
+        let suitable = weights.iter().filter(|&&weight| weight > 0.0).count();
+        let target = (suitable as f32 * rule.density).round() as u32;
+        let mut tree = WeightTree::new(weights);
+
+        // block hexes near already placed resources
+        for index in 0..hex_map.field.len() {
+            if hex_map.field[index].resource.is_some() {
+                for other in self.blocked_area(hex_map, index) {
+                    tree.set(other, 0.0);
+                }
+            }
+        }
+
+        for _ in 0..target {
+            let total = tree.total();
+            if total <= 0.0 {
+                break;
+            }
+            let index = match tree.find(rng.gen::<f32>() * total) {
+                Some(index) => index,
+                None => break
+            };
+            hex_map.field[index].resource = Some(rule.resource);
+
+            for other in self.blocked_area(hex_map, index) {
+                tree.set(other, 0.0);
+            }
+
+            // make hexes right behind the blocked area more likely
+            if rule.clustering > 0.0 {
+                let hex = hex_map.field[index];
+                let boost = 1.0 + rule.clustering * 4.0;
+                for radius in (self.min_spacing.max(1))..=(self.min_spacing.max(1) + 1) {
+                    for (x, y) in hex.get_ring(hex_map, radius) {
+                        if let Some(other) = checked_index(hex_map, x, y) {
+                            let weight = tree.get(other);
+                            if weight > 0.0 {
+                                tree.set(other, weight * boost);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Places missing guaranteed resources near start points
+    fn fair_pass(&self, hex_map: &mut HexMap) {
+        for &(start_x, start_y) in &self.start_points {
+            let start = Hex::from_coords(start_x, start_y);
+            let area = spiral_indices(hex_map, &start, self.fair_radius);
+
+            for resource in &self.guaranteed {
+                if area.iter().any(|&index| hex_map.field[index].resource == Some(*resource)) {
+                    continue;
+                }
+                let rule = match self.rules.iter().find(|rule| rule.resource == *resource) {
+                    Some(rule) => rule,
+                    None => continue
+                };
+
+                // prefer hexes respecting spacing, closer hexes win ties
+                let mut best = None;
+                let mut best_score = (false, 0.0);
+                for &index in &area {
+                    let hex = &hex_map.field[index];
+                    let affinity = rule.affinity(hex.terrain_type);
+                    if hex.resource.is_some() || affinity <= 0.0 {
+                        continue;
+                    }
+                    let spaced = self.blocked_area(hex_map, index).iter().all(|&other| hex_map.field[other].resource.is_none());
+                    if best.is_none() || (spaced, affinity) > best_score {
+                        best = Some(index);
+                        best_score = (spaced, affinity);
+                    }
+                }
+                match best {
+                    Some(index) => hex_map.field[index].resource = Some(*resource),
+                    None => debug_println!("No suitable hex for {:?} near {}, {}", resource, start_x, start_y)
+                }
+            }
+        }
+    }
+
+    /// Returns indices of hexes in which no other resource can be placed
+    fn blocked_area(&self, hex_map: &HexMap, index: usize) -> Vec<usize> {
+        if self.min_spacing <= 1 {
+            return vec![index];
+        }
+        spiral_indices(hex_map, &hex_map.field[index], self.min_spacing - 1)
+    }
+}
+
+impl Default for Resources {
+    fn default() -> Resources {
+        let rules = vec![
+            ResourceRule::new(Resource::Iron, &[(HexType::Mountain, 1.0), (HexType::Impassable, 0.4), (HexType::Tundra, 0.2)], 0.08, 0.3),
+            ResourceRule::new(Resource::Gold, &[(HexType::Mountain, 0.5), (HexType::Desert, 0.3), (HexType::Impassable, 0.3)], 0.04, 0.1),
+            ResourceRule::new(Resource::Horses, &[(HexType::Grassland, 1.0), (HexType::Field, 0.7)], 0.03, 0.5),
+            ResourceRule::new(Resource::Fish, &[(HexType::Water, 1.0), (HexType::Ocean, 0.2)], 0.03, 0.2),
+            ResourceRule::new(Resource::Spices, &[(HexType::Jungle, 1.0), (HexType::Swamp, 0.3), (HexType::Desert, 0.1)], 0.05, 0.6),
+        ];
+        Resources{rules, min_spacing: 2, start_points: Vec::new(), fair_radius: 5, guaranteed: Vec::new()}
+    }
+}
+
+impl Pass for Resources {
+    fn apply(&self, hex_map: &mut HexMap, seed: u32) {
+        let mut rng = StdRng::seed_from_u64(u64::from(seed));
+        for rule in &self.rules {
+            self.place_rule(hex_map, rule, &mut rng);
+        }
+        self.fair_pass(hex_map);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spacing_and_fairness() {
+        let mut hexmap = HexMap::new(40, 30);
+        for (index, hex) in hexmap.field.iter_mut().enumerate() {
+            hex.terrain_type = if index % 7 == 0 { HexType::Mountain } else { HexType::Field };
+        }
+        let resources = Resources{
+            min_spacing: 3,
+            start_points: vec![(5, 5)],
+            guaranteed: vec![Resource::Iron],
+            ..Default::default()
+        };
+        resources.apply(&mut hexmap, 3);
+
+        let placed = hexmap.field.iter().filter(|hex| hex.resource.is_some()).collect::<Vec<_>>();
+        assert!(!placed.is_empty());
+        for (index, hex) in placed.iter().enumerate() {
+            for other in &placed[index + 1..] {
+                assert!(hex.distance_between(other) >= 3);
+            }
+        }
+
+        let start = Hex::from_coords(5, 5);
+        assert!(placed.iter().any(|hex| hex.resource == Some(Resource::Iron) && hex.distance_between(&start) <= 5));
+    }
+}
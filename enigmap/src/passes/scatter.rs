@@ -0,0 +1,160 @@
+use rand::prelude::*;
+
+use crate::hexmap::HexMap;
+use crate::hex::{Decor, HexType};
+use crate::passes::{Pass, spiral_indices};
+
+/// Placement settings of single decor flag
+#[derive(Debug, Clone, Copy)]
+pub struct ScatterRule {
+    /// Placed flag
+    pub decor: Decor,
+    /// Returns `true` for `HexType`s on which the flag can be placed
+    pub filter: fn(HexType) -> bool,
+    /// Ratio of suitable hexes which get the flag
+    pub density: f32,
+    /// Minimal distance between two hexes with the flag, as in `Hex::distance_between`
+    pub min_distance: u32,
+    /// Flags which can't be on the same hex
+    pub excluded: Decor,
+}
+
+impl ScatterRule {
+    pub fn new(decor: Decor, filter: fn(HexType) -> bool, density: f32, min_distance: u32) -> ScatterRule {
+        ScatterRule{decor, filter, density, min_distance, excluded: Decor::empty()}
+    }
+
+    /// Returns `true` if the flag can be placed on the hex
+    fn is_suitable(&self, hex_type: HexType, decor: Decor) -> bool {
+        (self.filter)(hex_type) && !decor.intersects(self.excluded | self.decor)
+    }
+}
+
+/// Pass which scatters decor flags with Poisson-disk spacing
+///
+/// Flags already present on the map are respected, so the pass can be applied repeatedly
+/// ## Usage
+/// ```
+/// use enigmap::{prelude::*, Decor, HexType, generators::Islands, passes::{Pass, Scatter, ScatterRule}};
+///
+/// let mut hexmap = HexMap::new(100, 75);
+/// let gen = Islands::default();
+/// gen.generate(&mut hexmap);
+///
+/// let mut scatter = Scatter::default();
+/// let mut villages = ScatterRule::new(Decor::VILLAGE, |hex_type| hex_type == HexType::Field, 0.02, 5);
+/// villages.excluded = Decor::CITY | Decor::RUIN;
+/// scatter.rules.push(villages);
+/// scatter.apply(&mut hexmap, 42);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scatter {
+    /// Rules applied in order
+    pub rules: Vec<ScatterRule>,
+}
+
+impl Scatter {
+    /// Removes flag from all hexes
+    pub fn clear(hex_map: &mut HexMap, decor: Decor) {
+        for hex in &mut hex_map.field {
+            hex.decor.remove(decor);
+        }
+    }
+
+    fn place_rule(&self, hex_map: &mut HexMap, rule: &ScatterRule, rng: &mut StdRng) {
+        let mut candidates = hex_map.field.iter()
+            .enumerate()
+            .filter(|(_, hex)| rule.is_suitable(hex.terrain_type, hex.decor))
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+        let target = (candidates.len() as f32 * rule.density).round() as usize;
+        if target == 0 {
+            return;
+        }
+
+        // block surroundings of already placed flags
+        let mut blocked = vec![false; hex_map.field.len()];
+        for index in 0..hex_map.field.len() {
+            if hex_map.field[index].decor.contains(rule.decor) {
+                Self::block(hex_map, &mut blocked, rule, index);
+            }
+        }
+
+        // dart throwing over shuffled candidates
+        candidates.shuffle(rng);
+        let mut placed = 0;
+        for index in candidates {
+            if placed == target {
+                break;
+            }
+            if blocked[index] {
+                continue;
+            }
+            hex_map.field[index].decor.insert(rule.decor);
+            Self::block(hex_map, &mut blocked, rule, index);
+            placed += 1;
+        }
+
+        if placed < target {
+            debug_println!("Placed only {} of {} {:?}, spacing too big", placed, target, rule.decor);
+        }
+    }
+
+    fn block(hex_map: &HexMap, blocked: &mut [bool], rule: &ScatterRule, index: usize) {
+        blocked[index] = true;
+        if rule.min_distance > 1 {
+            for other in spiral_indices(hex_map, &hex_map.field[index], rule.min_distance - 1) {
+                blocked[other] = true;
+            }
+        }
+    }
+}
+
+impl Default for Scatter {
+    fn default() -> Scatter {
+        let mut ruins = ScatterRule::new(Decor::RUIN, |hex_type| matches!(hex_type,
+            HexType::Field | HexType::Forest | HexType::Desert | HexType::Tundra | HexType::Jungle | HexType::Grassland
+        ), 0.01, 6);
+        ruins.excluded = Decor::CITY | Decor::VILLAGE;
+        let mut hills = ScatterRule::new(Decor::HILL, |hex_type| matches!(hex_type,
+            HexType::Field | HexType::Forest | HexType::Desert | HexType::Tundra | HexType::Grassland
+        ), 0.06, 2);
+        hills.excluded = Decor::CITY;
+        Scatter{rules: vec![ruins, hills]}
+    }
+}
+
+impl Pass for Scatter {
+    fn apply(&self, hex_map: &mut HexMap, seed: u32) {
+        let mut rng = StdRng::seed_from_u64(u64::from(seed));
+        for rule in &self.rules {
+            self.place_rule(hex_map, rule, &mut rng);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spacing_and_exclusion() {
+        let mut hexmap = HexMap::new(40, 30);
+        hexmap.fill(HexType::Field);
+        for hex in hexmap.field.iter_mut().step_by(5) {
+            hex.decor = Decor::CITY;
+        }
+        let mut rule = ScatterRule::new(Decor::RUIN, |hex_type| hex_type == HexType::Field, 1.0, 3);
+        rule.excluded = Decor::CITY;
+        Scatter{rules: vec![rule]}.apply(&mut hexmap, 11);
+
+        let ruins = hexmap.field.iter().filter(|hex| hex.decor.contains(Decor::RUIN)).collect::<Vec<_>>();
+        assert!(!ruins.is_empty());
+        for (i, ruin) in ruins.iter().enumerate() {
+            assert!(!ruin.decor.contains(Decor::CITY));
+            for other in &ruins[i + 1..] {
+                assert!(ruin.distance_between(other) >= 3);
+            }
+        }
+    }
+}
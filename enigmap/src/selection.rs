@@ -0,0 +1,214 @@
+use crate::hexmap::HexMap;
+use crate::hex::{Hex, HexType};
+
+use std::collections::{HashMap, VecDeque};
+
+/// Set of hexes in specific `HexMap`
+///
+/// Used for limiting generators to some part of the map
+#[derive(Debug, Clone)]
+pub struct Selection {
+    size_x: u32,
+    size_y: u32,
+    selected: Vec<bool>,
+}
+
+impl Selection {
+    /// Creates empty selection for given map
+    pub fn new(hex_map: &HexMap) -> Selection {
+        Selection{size_x: hex_map.size_x, size_y: hex_map.size_y, selected: vec![false; hex_map.field.len()]}
+    }
+
+    /// Creates selection from coordinates, e.g. from `Hex::get_spiral`
+    ///
+    /// Coordinates outside of the map are ignored
+    pub fn from_coords(hex_map: &HexMap, coords: &[(i32, i32)]) -> Selection {
+        let mut selection = Selection::new(hex_map);
+        for &(x, y) in coords {
+            selection.add(hex_map, x, y);
+        }
+        selection
+    }
+
+    /// Creates rectangular selection
+    ///
+    /// `column` and `row` are position in `HexMap::field` grid, not `Hex` coordinates.
+    /// Columns wrap around the X axis, rows are clipped.
+    pub fn rectangle(hex_map: &HexMap, column: u32, row: u32, width: u32, height: u32) -> Selection {
+        let mut selection = Selection::new(hex_map);
+        let width = width.min(hex_map.size_x);
+        for y in row..(row + height).min(hex_map.size_y) {
+            for x in column..(column + width) {
+                let index = (y * hex_map.size_x + x % hex_map.size_x) as usize;
+                selection.selected[index] = true;
+            }
+        }
+        selection
+    }
+
+    /// Creates selection of all hexes for which the predicate returns `true`
+    pub fn from_predicate<F>(hex_map: &HexMap, predicate: F) -> Selection
+        where F: Fn(&Hex) -> bool
+    {
+        let selected = hex_map.field.iter().map(predicate).collect();
+        Selection{size_x: hex_map.size_x, size_y: hex_map.size_y, selected}
+    }
+
+    /// Adds hex to the selection
+    pub fn add(&mut self, hex_map: &HexMap, x: i32, y: i32) {
+        if let Some(index) = self.index(hex_map, x, y) {
+            self.selected[index] = true;
+        }
+    }
+
+    /// Removes hex from the selection
+    pub fn remove(&mut self, hex_map: &HexMap, x: i32, y: i32) {
+        if let Some(index) = self.index(hex_map, x, y) {
+            self.selected[index] = false;
+        }
+    }
+
+    /// Returns `true` if hex on given coordinates is selected
+    pub fn contains(&self, hex_map: &HexMap, x: i32, y: i32) -> bool {
+        match self.index(hex_map, x, y) {
+            Some(index) => self.selected[index],
+            None => false
+        }
+    }
+
+    /// Returns `true` if hex on given index in `HexMap::field` is selected
+    pub fn contains_index(&self, index: usize) -> bool {
+        self.selected.get(index).copied().unwrap_or(false)
+    }
+
+    /// Returns number of selected hexes
+    pub fn len(&self) -> usize {
+        self.selected.iter().filter(|&&selected| selected).count()
+    }
+
+    /// Returns `true` if no hex is selected
+    pub fn is_empty(&self) -> bool {
+        !self.selected.iter().any(|&selected| selected)
+    }
+
+    /// Checks if selection was created for map of the same size
+    pub fn matches(&self, hex_map: &HexMap) -> bool {
+        self.size_x == hex_map.size_x && self.size_y == hex_map.size_y
+    }
+
+    fn index(&self, hex_map: &HexMap, x: i32, y: i32) -> Option<usize> {
+        if y < 0 || y >= hex_map.size_y as i32 {
+            return None;
+        }
+        let (x, y) = Hex::unwrap_coords(x, y, hex_map.size_x);
+        hex_map.coords_to_index(x, y)
+    }
+}
+
+/// Smooths selected hexes near the border of the selection
+///
+/// Hexes with more than 3 neighbours of different type take the most common type of their neighbours
+pub fn blend_border(hex_map: &mut HexMap, selection: &Selection, blend_width: u32) {
+    if blend_width == 0 {
+        return;
+    }
+
+    // get distance to the border from inside of the selection
+    let mut distances = vec![None; hex_map.field.len()];
+    let mut queue = VecDeque::new();
+    for (index, hex) in hex_map.field.iter().enumerate() {
+        if !selection.contains_index(index) {
+            continue;
+        }
+        let on_border = hex.get_neighbours(hex_map).iter().any(|&(x, y)| {
+            !selection.contains(hex_map, x, y)
+        });
+        if on_border {
+            distances[index] = Some(1);
+            queue.push_back(index);
+        }
+    }
+    while let Some(index) = queue.pop_front() {
+        let distance = distances[index].unwrap();
+        if distance >= blend_width {
+            continue;
+        }
+        for (x, y) in hex_map.field[index].get_neighbours(hex_map) {
+            let other = hex_map.coords_to_index(x, y).unwrap();
+            if selection.contains_index(other) && distances[other].is_none() {
+                distances[other] = Some(distance + 1);
+                queue.push_back(other);
+            }
+        }
+    }
+
+    for _ in 0..blend_width {
+        let old_map = hex_map.clone();
+        for (index, hex) in hex_map.field.iter_mut().enumerate() {
+            if distances[index].is_none() {
+                continue;
+            }
+            let mut counts: HashMap<HexType, u32> = HashMap::new();
+            let mut diff_neighbours = 0;
+            for (neighbour_x, neighbour_y) in hex.get_neighbours(&old_map) {
+                let neighbour = old_map.field[old_map.coords_to_index(neighbour_x, neighbour_y).unwrap()].terrain_type;
+                if neighbour != hex.terrain_type {
+                    diff_neighbours += 1;
+                }
+                *counts.entry(neighbour).or_insert(0) += 1;
+            }
+            if diff_neighbours > 3 {
+                // ties are resolved by type index to keep the result deterministic
+                let most_common = counts.into_iter().max_by_key(|&(hex_type, count)| (count, -i32::from(hex_type)));
+                if let Some((hex_type, _)) = most_common {
+                    hex.terrain_type = hex_type;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::HexType;
+    use crate::generators::{MapGen, Circle};
+
+    #[test]
+    fn generate_area() {
+        let mut hexmap = HexMap::new(40, 30);
+        hexmap.fill(HexType::Swamp);
+        let selection = Selection::rectangle(&hexmap, 35, 10, 10, 10);
+        assert_eq!(100, selection.len());
+
+        let mut gen = Circle::new_optimized(&hexmap);
+        gen.set_seed(7);
+        let mut generated = hexmap.clone();
+        gen.generate(&mut generated);
+        let mut unblended = hexmap.clone();
+        gen.generate_area(&mut unblended, &selection, 0);
+        gen.generate_area(&mut hexmap, &selection, 2);
+
+        // without blending the selection is exactly the generated map
+        for (index, hex) in unblended.field.iter().enumerate() {
+            if selection.contains_index(index) {
+                assert_eq!(generated.field[index].terrain_type, hex.terrain_type);
+            } else {
+                assert_eq!(HexType::Swamp, hex.terrain_type);
+            }
+        }
+        assert!(unblended.field.iter().enumerate().any(|(index, hex)| selection.contains_index(index) && hex.terrain_type != HexType::Swamp));
+
+        // blending changes only the rim, interior stays generated
+        let interior = Selection::rectangle(&hexmap, 38, 13, 4, 4);
+        let mut rim_changed = false;
+        for (index, hex) in hexmap.field.iter().enumerate() {
+            if !selection.contains_index(index) || interior.contains_index(index) {
+                assert_eq!(unblended.field[index].terrain_type, hex.terrain_type);
+            } else if unblended.field[index].terrain_type != hex.terrain_type {
+                rim_changed = true;
+            }
+        }
+        assert!(rim_changed);
+    }
+}
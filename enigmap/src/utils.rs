@@ -1,5 +1,7 @@
 use std::env;
 
+use crate::hex::Hex;
+
 /// Checks for debug envorinment variable `ENIGMAP_DEBUG` and returns debug state
 pub fn check_debug() -> bool {
     // get env variable as a Option<OsString>
@@ -23,6 +25,24 @@ pub fn check_debug() -> bool {
     }
 }
 
+/// Maps every hex in the field with its index
+///
+/// With `parallel` feature the field is split into chunks processed on multiple threads.
+/// Results are always in the same order as the field, so the output doesn't depend on the feature.
+pub(crate) fn map_field<T, F>(field: &[Hex], f: F) -> Vec<T>
+    where T: Send, F: Fn(usize, &Hex) -> T + Sync + Send
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        field.par_iter().enumerate().with_min_len(256).map(|(index, hex)| f(index, hex)).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        field.iter().enumerate().map(|(index, hex)| f(index, hex)).collect()
+    }
+}
+
 #[macro_export]
 #[doc(hidden)]
 macro_rules! debug_println {
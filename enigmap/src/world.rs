@@ -0,0 +1,268 @@
+use noise::{Fbm, Perlin, NoiseFn, Seedable};
+
+use std::collections::HashMap;
+
+use crate::hexmap::HexMap;
+use crate::hex::{Hex, HexType, RATIO};
+use crate::utils::map_field;
+
+/// Trait for generators of chunked worlds
+///
+/// Chunks must be generated only from their position, so they join seamlessly and regenerate identically
+pub trait ChunkGen {
+    /// Generates single chunk
+    fn generate_chunk(&self, chunk: &mut HexMap, position: &ChunkPosition);
+}
+
+/// Position of the chunk in the world
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChunkPosition {
+    /// Chunk coordinate on X axis
+    pub x: i32,
+    /// Chunk coordinate on Y axis
+    pub y: i32,
+    /// Seed of the whole world
+    pub world_seed: u32,
+    /// Absolute position of the chunk origin in the world
+    ///
+    /// Add this to `Hex::center` to get global position of the hex for sampling noise
+    pub offset: (f32, f32),
+}
+
+impl ChunkPosition {
+    /// Returns stable seed for this chunk, can be used for chunk specific random generators
+    pub fn seed(&self) -> u32 {
+        // splitmix64 finalizer
+        let mix = |value: u64| {
+            let mut hash = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            hash ^ (hash >> 31)
+        };
+        let hash = mix(u64::from(self.world_seed));
+        let hash = mix(hash ^ u64::from(self.x as u32));
+        let hash = mix(hash ^ u64::from(self.y as u32));
+        hash as u32
+    }
+
+    /// Returns global position of hex center
+    pub fn global_center(&self, hex: &Hex) -> (f32, f32) {
+        let (center_x, center_y) = hex.center();
+        (center_x + self.offset.0, center_y + self.offset.1)
+    }
+}
+
+/// World made from `HexMap` chunks generated on demand
+///
+/// Hexes in chunks use local coordinates. Chunks are cached until unloaded.
+/// ## Usage
+/// ```
+/// use enigmap::world::{World, NoiseChunks};
+///
+/// let mut world = World::new(NoiseChunks::default(), 42, 32, 32);
+/// let chunk = world.chunk(-3, 5);
+/// assert_eq!(32 * 32, chunk.field.len());
+/// world.unload_chunk(-3, 5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct World<G: ChunkGen> {
+    seed: u32,
+    chunk_size_x: u32,
+    chunk_size_y: u32,
+    generator: G,
+    chunks: HashMap<(i32, i32), HexMap>,
+}
+
+impl<G: ChunkGen> World<G> {
+    /// Creates new world
+    /// # Panics
+    /// when chunk dimension is 0 or when `chunk_size_y` is odd, which would break the hex grid between chunks
+    pub fn new(generator: G, seed: u32, chunk_size_x: u32, chunk_size_y: u32) -> World<G> {
+        if chunk_size_x == 0 || chunk_size_y == 0 {
+            panic!("One of chunk dimensions is 0");
+        }
+        if chunk_size_y & 1 == 1 {
+            panic!("Chunk size on Y axis must be even");
+        }
+        World{seed, chunk_size_x, chunk_size_y, generator, chunks: HashMap::new()}
+    }
+
+    /// Returns seed of the world
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Returns size of chunks
+    pub fn chunk_size(&self) -> (u32, u32) {
+        (self.chunk_size_x, self.chunk_size_y)
+    }
+
+    /// Returns reference to used generator
+    pub fn generator(&self) -> &G {
+        &self.generator
+    }
+
+    /// Returns position of specified chunk
+    pub fn chunk_position(&self, chunk_x: i32, chunk_y: i32) -> ChunkPosition {
+        const THREE_QUARTER_RATIO: f32 = RATIO * 3.0 / 4.0;
+        let offset = (
+            chunk_x as f32 * self.chunk_size_x as f32,
+            chunk_y as f32 * self.chunk_size_y as f32 * THREE_QUARTER_RATIO
+        );
+        ChunkPosition{x: chunk_x, y: chunk_y, world_seed: self.seed, offset}
+    }
+
+    /// Generates chunk without caching it
+    pub fn generate_chunk(&self, chunk_x: i32, chunk_y: i32) -> HexMap {
+        let mut chunk = HexMap::new(self.chunk_size_x, self.chunk_size_y);
+        self.generator.generate_chunk(&mut chunk, &self.chunk_position(chunk_x, chunk_y));
+        chunk
+    }
+
+    /// Returns chunk, generates it if it is not loaded
+    pub fn chunk(&mut self, chunk_x: i32, chunk_y: i32) -> &HexMap {
+        if !self.chunks.contains_key(&(chunk_x, chunk_y)) {
+            let chunk = self.generate_chunk(chunk_x, chunk_y);
+            self.chunks.insert((chunk_x, chunk_y), chunk);
+        }
+        &self.chunks[&(chunk_x, chunk_y)]
+    }
+
+    /// Returns mutable chunk, generates it if it is not loaded
+    ///
+    /// Changes are lost when the chunk is unloaded
+    pub fn chunk_mut(&mut self, chunk_x: i32, chunk_y: i32) -> &mut HexMap {
+        if !self.chunks.contains_key(&(chunk_x, chunk_y)) {
+            let chunk = self.generate_chunk(chunk_x, chunk_y);
+            self.chunks.insert((chunk_x, chunk_y), chunk);
+        }
+        self.chunks.get_mut(&(chunk_x, chunk_y)).unwrap()
+    }
+
+    /// Returns chunk only if it is loaded
+    pub fn get_loaded_chunk(&self, chunk_x: i32, chunk_y: i32) -> Option<&HexMap> {
+        self.chunks.get(&(chunk_x, chunk_y))
+    }
+
+    /// Returns hex at global grid position, generates chunk if needed
+    ///
+    /// `column` and `row` are positions in grid of all chunks, not `Hex` coordinates
+    pub fn get_hex(&mut self, column: i32, row: i32) -> &Hex {
+        let chunk_x = column.div_euclid(self.chunk_size_x as i32);
+        let chunk_y = row.div_euclid(self.chunk_size_y as i32);
+        let local_x = column.rem_euclid(self.chunk_size_x as i32) as u32;
+        let local_y = row.rem_euclid(self.chunk_size_y as i32) as u32;
+        let size_x = self.chunk_size_x;
+        &self.chunk(chunk_x, chunk_y).field[(local_x + local_y * size_x) as usize]
+    }
+
+    /// Removes chunk from cache
+    pub fn unload_chunk(&mut self, chunk_x: i32, chunk_y: i32) -> Option<HexMap> {
+        self.chunks.remove(&(chunk_x, chunk_y))
+    }
+
+    /// Removes all chunks further than `distance` chunks from specified chunk
+    pub fn unload_distant(&mut self, chunk_x: i32, chunk_y: i32, distance: u32) {
+        let distance = distance as i32;
+        self.chunks.retain(|&(x, y), _| (x - chunk_x).abs() <= distance && (y - chunk_y).abs() <= distance);
+    }
+
+    /// Returns coordinates of all loaded chunks
+    pub fn loaded_chunks(&self) -> Vec<(i32, i32)> {
+        self.chunks.keys().copied().collect()
+    }
+}
+
+/// Chunk generator using noise sampled in global coordinates
+#[derive(Debug, Clone, Copy)]
+pub struct NoiseChunks {
+    /// Scale of elevation noise
+    pub noise_scale: f64,
+    /// Scale of temperature and humidity noise, should be lower than `noise_scale`
+    pub climate_scale: f64,
+    /// Elevation under which hexes are water, in range `<-1; 1>`
+    pub sea_level: f64,
+    /// Depth under `sea_level` from which water becomes ocean
+    pub shallow_depth: f64,
+    /// Elevation from which hexes are mountains
+    pub mountain_level: f64,
+}
+
+impl Default for NoiseChunks {
+    fn default() -> NoiseChunks {
+        NoiseChunks{noise_scale: 0.03, climate_scale: 0.008, sea_level: 0.0, shallow_depth: 0.1, mountain_level: 0.45}
+    }
+}
+
+impl ChunkGen for NoiseChunks {
+    fn generate_chunk(&self, chunk: &mut HexMap, position: &ChunkPosition) {
+        let elevation = Fbm::new().set_seed(position.world_seed);
+        let temperature = Perlin::new().set_seed(position.world_seed.wrapping_add(1));
+        let humidity = Perlin::new().set_seed(position.world_seed.wrapping_add(2));
+
+        let types = map_field(&chunk.field, |_, hex| {
+            let (x, y) = position.global_center(hex);
+            let (x, y) = (f64::from(x), f64::from(y));
+            let height = elevation.get([x * self.noise_scale, y * self.noise_scale]);
+            let temp = temperature.get([x * self.climate_scale, y * self.climate_scale]);
+            let hum = humidity.get([x * self.climate_scale, y * self.climate_scale]);
+
+            if height < self.sea_level - self.shallow_depth {
+                if temp < -0.5 { HexType::Ice } else { HexType::Ocean }
+            } else if height < self.sea_level {
+                HexType::Water
+            } else if height > self.mountain_level {
+                HexType::Mountain
+            } else if temp < -0.45 {
+                HexType::Ice
+            } else if temp < -0.25 {
+                HexType::Tundra
+            } else if temp > 0.3 && hum < -0.1 {
+                HexType::Desert
+            } else if temp > 0.3 && hum > 0.2 {
+                HexType::Jungle
+            } else if hum > 0.35 {
+                HexType::Swamp
+            } else if hum > 0.0 {
+                HexType::Forest
+            } else if temp > 0.1 {
+                HexType::Grassland
+            } else {
+                HexType::Field
+            }
+        });
+        for (hex, terrain_type) in chunk.field.iter_mut().zip(types) {
+            hex.terrain_type = terrain_type;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seamless_chunks() {
+        let small = World::new(NoiseChunks::default(), 5, 8, 8);
+        let big = World::new(NoiseChunks::default(), 5, 16, 8);
+
+        let left = small.generate_chunk(-2, -1);
+        let right = small.generate_chunk(-1, -1);
+        let whole = big.generate_chunk(-1, -1);
+
+        for row in 0..8 {
+            for column in 0..16 {
+                let expected = whole.field[row * 16 + column].terrain_type;
+                let hex = if column < 8 {
+                    left.field[row * 8 + column]
+                } else {
+                    right.field[row * 8 + column - 8]
+                };
+                assert_eq!(expected, hex.terrain_type);
+            }
+        }
+
+        assert_eq!(small.chunk_position(3, 4).seed(), small.chunk_position(3, 4).seed());
+        assert_ne!(small.chunk_position(3, 4).seed(), small.chunk_position(4, 3).seed());
+    }
+}
@@ -0,0 +1,45 @@
+use enigmap::prelude::*;
+use enigmap::generators::*;
+
+/// FNV-1a hash of terrain, decor and resources of every hex
+fn map_hash(map: &HexMap) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for hex in &map.field {
+        let mut bytes = i32::from(hex.terrain_type).to_le_bytes().to_vec();
+        bytes.push(hex.decor.bits());
+        bytes.push(hex.resource.map_or(0, |resource| resource as u8 + 1));
+        for byte in bytes {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Generates maps with seeds 0, 1 and 2
+fn hashes<G: MapGen>(mut gen: G) -> Vec<u64> {
+    (0..3).map(|seed| {
+        let mut map = HexMap::new(60, 40);
+        gen.set_seed(seed);
+        gen.generate(&mut map);
+        map_hash(&map)
+    }).collect()
+}
+
+// hashes are taken from serial generation, run with `--features parallel` to check that threads don't change the output
+
+#[test]
+fn islands_match_serial() {
+    assert_eq!(vec![5_173_124_351_904_490_144, 4_486_644_183_063_218_571, 3_280_424_992_531_176_927], hashes(Islands::default()));
+}
+
+#[test]
+fn circle_match_serial() {
+    let map = HexMap::new(60, 40);
+    assert_eq!(vec![226_812_326_513_802_440, 15_513_776_404_354_901_314, 4_433_491_027_408_140_257], hashes(Circle::new_optimized(&map)));
+}
+
+#[test]
+fn inland_match_serial() {
+    assert_eq!(vec![15_638_008_682_239_491_351, 9_104_700_751_468_506_163, 14_034_030_052_871_905_865], hashes(Inland::default()));
+}